@@ -0,0 +1,224 @@
+//! Finding where applying the same function over and over starts repeating itself
+use std::{
+    collections::{hash_map::DefaultHasher, HashMap},
+    hash::{Hash, Hasher},
+};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Cycle {
+    /// Number of steps before the first state that comes back
+    pub prefix: usize,
+    /// Number of steps it takes to come back
+    pub period: usize,
+}
+
+impl Cycle {
+    /// The first step that has the same state as the given one
+    pub fn equivalent_step(&self, step: usize) -> usize {
+        if step < self.prefix {
+            step
+        } else {
+            self.prefix + (step - self.prefix) % self.period
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Method {
+    /// Tortoise and hare, keeps two states but calls the function about three times as often as
+    /// there are steps before it knows
+    Floyd,
+    /// Teleporting tortoise, keeps two states and calls the function less often than Floyd
+    Brent,
+    /// Remembers a fingerprint of every state, calls the function once for every step
+    Hash,
+}
+
+/// Where the states start, step by step from the start, repeating. The sequence has to repeat at
+/// some point, otherwise this never returns.
+pub fn find_cycle<S, F>(start: &S, step: F, method: Method) -> Cycle
+where
+    S: Clone + Eq + Hash,
+    F: FnMut(&S) -> S,
+{
+    match method {
+        Method::Floyd => floyd(start, step),
+        Method::Brent => brent(start, step),
+        Method::Hash => hashed(start, step),
+    }
+}
+
+/// The state after n steps, without taking all of them
+pub fn state_at<S, F>(start: &S, mut step: F, n: usize, method: Method) -> S
+where
+    S: Clone + Eq + Hash,
+    F: FnMut(&S) -> S,
+{
+    let cycle = find_cycle(start, &mut step, method);
+    run(start, &mut step, cycle.equivalent_step(n))
+}
+
+fn run<S, F>(start: &S, mut step: F, n: usize) -> S
+where
+    S: Clone,
+    F: FnMut(&S) -> S,
+{
+    (0..n).fold(start.clone(), |state, _| step(&state))
+}
+
+fn floyd<S, F>(start: &S, mut step: F) -> Cycle
+where
+    S: Clone + Eq,
+    F: FnMut(&S) -> S,
+{
+    // the hare goes twice as fast, so they meet somewhere in the cycle
+    let mut tortoise = step(start);
+    let mut hare = step(&tortoise);
+    while tortoise != hare {
+        tortoise = step(&tortoise);
+        let halfway = step(&hare);
+        hare = step(&halfway);
+    }
+    // the hare is a multiple of the period ahead, so starting over they meet where it starts
+    let mut prefix = 0;
+    tortoise = start.clone();
+    while tortoise != hare {
+        tortoise = step(&tortoise);
+        hare = step(&hare);
+        prefix += 1;
+    }
+    let mut period = 1;
+    hare = step(&tortoise);
+    while tortoise != hare {
+        hare = step(&hare);
+        period += 1;
+    }
+    Cycle { prefix, period }
+}
+
+fn brent<S, F>(start: &S, mut step: F) -> Cycle
+where
+    S: Clone + Eq,
+    F: FnMut(&S) -> S,
+{
+    // the tortoise jumps to the hare every power of two, until the hare catches up with it
+    let mut power = 1;
+    let mut period = 1;
+    let mut tortoise = start.clone();
+    let mut hare = step(start);
+    while tortoise != hare {
+        if power == period {
+            tortoise = hare.clone();
+            power *= 2;
+            period = 0;
+        }
+        hare = step(&hare);
+        period += 1;
+    }
+    // with the hare one period ahead, they meet where it starts
+    let mut prefix = 0;
+    tortoise = start.clone();
+    hare = run(start, &mut step, period);
+    while tortoise != hare {
+        tortoise = step(&tortoise);
+        hare = step(&hare);
+        prefix += 1;
+    }
+    Cycle { prefix, period }
+}
+
+fn hashed<S, F>(start: &S, mut step: F) -> Cycle
+where
+    S: Clone + Eq + Hash,
+    F: FnMut(&S) -> S,
+{
+    // the steps with every fingerprint, more than one only if different states collide
+    let mut seen: HashMap<u64, Vec<usize>> = HashMap::new();
+    let mut state = start.clone();
+    for n in 0.. {
+        let steps = seen.entry(fingerprint(&state)).or_default();
+        // fingerprints can collide, so check it's the same state before believing it
+        if let Some(&prefix) = steps.iter().find(|&&s| run(start, &mut step, s) == state) {
+            return Cycle {
+                prefix,
+                period: n - prefix,
+            };
+        }
+        steps.push(n);
+        state = step(&state);
+    }
+    unreachable!("The states never repeat")
+}
+
+fn fingerprint<S: Hash>(state: &S) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    state.hash(&mut hasher);
+    hasher.finish()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const METHODS: [Method; 3] = [Method::Floyd, Method::Brent, Method::Hash];
+
+    #[test]
+    fn find_cycles() {
+        // 0, 1, 2, 3, 4, 5, 3, 4, 5, ...
+        let step = |&n: &u32| if n == 5 { 3 } else { n + 1 };
+        for method in METHODS {
+            let cycle = find_cycle(&0, step, method);
+            assert_eq!(
+                cycle,
+                Cycle {
+                    prefix: 3,
+                    period: 3
+                },
+                "{method:?}"
+            );
+            assert_eq!(state_at(&0, step, 1_000_000_000, method), 4);
+            // starting in the cycle
+            assert_eq!(
+                find_cycle(&4, step, method),
+                Cycle {
+                    prefix: 0,
+                    period: 3
+                }
+            );
+            assert_eq!(
+                find_cycle(&7, |_: &u32| 7, method),
+                Cycle {
+                    prefix: 0,
+                    period: 1
+                }
+            );
+        }
+    }
+
+    #[test]
+    fn methods_agree() {
+        let step = |&n: &u64| (n * n + 12345) % 1_000_003;
+        let cycles = METHODS.map(|method| find_cycle(&2, step, method));
+        assert_eq!(cycles[0], cycles[1]);
+        assert_eq!(cycles[0], cycles[2]);
+        let Cycle { prefix, period } = cycles[0];
+        let first = run(&2, step, prefix);
+        assert_eq!(run(&first, step, period), first);
+        assert_ne!(
+            run(&2, step, prefix - 1),
+            run(&2, step, prefix - 1 + period)
+        );
+    }
+
+    #[test]
+    fn equivalent_step() {
+        let cycle = Cycle {
+            prefix: 3,
+            period: 4,
+        };
+        assert_eq!(cycle.equivalent_step(2), 2);
+        assert_eq!(cycle.equivalent_step(3), 3);
+        assert_eq!(cycle.equivalent_step(7), 3);
+        assert_eq!(cycle.equivalent_step(12), 4);
+    }
+}
@@ -6,26 +6,67 @@ use nom::{
 };
 
 pub fn process_part1(input: &str) -> String {
-    let (input, patterns) = parse_input(input).unwrap();
-    assert!(input.is_empty());
-    patterns
-        .into_iter()
-        .map(|p| p.score_part_1())
-        .sum::<usize>()
-        .to_string()
+    summarise(input, 0).to_string()
 }
 
 pub fn process_part2(input: &str) -> String {
+    summarise(input, 1).to_string()
+}
+
+/// Columns left of vertical mirrors plus 100 times the rows above horizontal mirrors, using the
+/// first mirror of every pattern
+fn summarise(input: &str, smudges: usize) -> usize {
+    find_reflections(input, smudges)
+        .iter()
+        .map(|reflections| {
+            reflections
+                .first()
+                .expect("Every pattern should have a mirror")
+                .mirror
+                .score()
+        })
+        .sum()
+}
+
+/// Every mirror of every pattern that has exactly `smudges` cells that don't match their
+/// reflection, horizontal mirrors first
+pub fn find_reflections(input: &str, smudges: usize) -> Vec<Vec<Reflection>> {
     let (input, patterns) = parse_input(input).unwrap();
     assert!(input.is_empty());
     patterns
-        .into_iter()
-        .map(|p| p.score_part_2())
-        .sum::<usize>()
-        .to_string()
+        .iter()
+        .map(|pattern| pattern.reflections(smudges))
+        .collect()
+}
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum Mirror {
+    /// Between two rows, with this many rows above it
+    Horizontal(usize),
+    /// Between two columns, with this many columns left of it
+    Vertical(usize),
 }
 
-#[derive(Debug, PartialEq, Eq)]
+impl Mirror {
+    pub fn score(&self) -> usize {
+        match self {
+            Mirror::Horizontal(rows) => rows * 100,
+            Mirror::Vertical(columns) => *columns,
+        }
+    }
+}
+
+/// The (x, y) of a cell that doesn't match its reflection and of the reflection, the one above or
+/// left of the mirror first
+pub type Mismatch = [(usize, usize); 2];
+
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct Reflection {
+    pub mirror: Mirror,
+    pub mismatches: Vec<Mismatch>,
+}
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
 enum Location {
     Ash,
     Rock,
@@ -47,129 +88,31 @@ struct Pattern {
 }
 
 impl Pattern {
-    fn score_part_1(&self) -> usize {
-        let mut total = 0;
-        // check top/bottom reflection (horizontal mirror)
-        if let Err(n) = self
-            .field
-            .windows(2)
-            .enumerate()
-            .try_fold(0, |_, (i, win)| {
-                if win[0] == win[1] {
-                    // start of reflection, maybe? Check the rest
-                    if self
-                        .field
-                        .iter()
-                        .skip(i + 1)
-                        .zip(self.field.iter().rev().skip(self.field.len() - i - 1))
-                        .all(|(l, r)| l == r)
-                    {
-                        Err(i + 1)
-                    } else {
-                        Ok(0)
-                    }
-                } else {
-                    Ok(0)
-                }
-            })
-        {
-            total += n * 100;
-        }
-        // check left/right reflection (vertical mirror)
-        else if let Err(n) = (0..(self.width() - 1)).try_fold(0, |_, col| {
-            if self.field.iter().all(|row| row[col] == row[col + 1]) {
-                // two columns match, does the rest?
-                if (0..col)
-                    .rev()
-                    .zip((col + 2)..self.width())
-                    .all(|(l, r)| self.field.iter().all(|row| row[l] == row[r]))
-                {
-                    Err(col + 1)
-                } else {
-                    Ok(0)
-                }
-            } else {
-                Ok(0)
-            }
-        }) {
-            total += n;
-        }
-
-        total
+    fn reflections(&self, smudges: usize) -> Vec<Reflection> {
+        let horizontal =
+            mirrors_between_rows(&self.field, smudges)
+                .into_iter()
+                .map(|(rows, mismatches)| Reflection {
+                    mirror: Mirror::Horizontal(rows),
+                    mismatches,
+                });
+        // columns are the rows of the transposed field, so x and y swap places
+        let vertical = mirrors_between_rows(&self.transposed(), smudges)
+            .into_iter()
+            .map(|(columns, mismatches)| Reflection {
+                mirror: Mirror::Vertical(columns),
+                mismatches: mismatches
+                    .into_iter()
+                    .map(|cells| cells.map(|(x, y)| (y, x)))
+                    .collect(),
+            });
+        horizontal.chain(vertical).collect()
     }
 
-    fn score_part_2(&self) -> usize {
-        let mut total = 0;
-        // check top/bottom reflection (horizontal mirror)
-        if let Err(n) = (0..(self.height() - 1)).try_fold(0_usize, |_, row| {
-            if self.match_rows(row, row + 1) {
-                // we've matched without a smudge, see if the rest matches with one smudge
-                if (0..row)
-                    .rev()
-                    .zip((row + 2)..self.height())
-                    .map(|(first, second)| self.match_rows_with_smudge(first, second))
-                    .sum::<usize>()
-                    == 1
-                {
-                    Err(row + 1)
-                } else {
-                    Ok(0)
-                }
-            } else if self.match_rows_with_smudge(row, row + 1) == 1 {
-                // we've matched with a smudge, see if the rest matches without a smudge
-                if (0..row)
-                    .rev()
-                    .zip((row + 2)..self.height())
-                    .all(|(first, second)| self.match_rows(first, second))
-                {
-                    Err(row + 1)
-                } else {
-                    Ok(0)
-                }
-            } else {
-                Ok(0)
-            }
-        }) {
-            total += n * 100;
-        }
-        // check left/right reflection (vertical mirror)
-        else if let Err(n) = (0..(self.width() - 1)).try_fold(0, |_, col| {
-            if self.match_columns(col, col + 1) {
-                // we've matched without a smudge, see if the rest matches with one smudge
-                if (0..col)
-                    .rev()
-                    .zip((col + 2)..self.width())
-                    .map(|(first, second)| self.match_columns_with_smudge(first, second))
-                    .sum::<usize>()
-                    == 1
-                {
-                    Err(col + 1)
-                } else {
-                    Ok(0)
-                }
-            } else if self.match_columns_with_smudge(col, col + 1) == 1 {
-                // we've matched with a smudge, see if the rest matches without a smudge
-                if (0..col)
-                    .rev()
-                    .zip((col + 2)..self.width())
-                    .all(|(first, second)| self.match_columns(first, second))
-                {
-                    Err(col + 1)
-                } else {
-                    Ok(0)
-                }
-            } else {
-                Ok(0)
-            }
-        }) {
-            total += n;
-        }
-
-        total
-    }
-
-    fn height(&self) -> usize {
-        self.field.len()
+    fn transposed(&self) -> Vec<Vec<Location>> {
+        (0..self.width())
+            .map(|x| self.field.iter().map(|row| row[x]).collect())
+            .collect()
     }
 
     fn width(&self) -> usize {
@@ -178,49 +121,28 @@ impl Pattern {
             .expect("Field must have dimensions!")
             .len()
     }
+}
 
-    fn match_rows(&self, first: usize, second: usize) -> bool {
-        assert!(
-            first < self.field.len() && second < self.field.len(),
-            "Rows to check out of bounds, the len was {} and the rows were {first} and {second}.",
-            self.field.len()
-        );
-        self.field[first] == self.field[second]
-    }
-
-    fn match_rows_with_smudge(&self, first: usize, second: usize) -> usize {
-        assert!(
-            first < self.field.len() && second < self.field.len(),
-            "Rows to check out of bounds, the len was {} and the rows were {first} and {second}.",
-            self.field.len()
-        );
-        let mismatches = self.field[first]
-            .iter()
-            .zip(self.field[second].iter())
-            .fold(0_usize, |acc, (l, r)| if l == r { acc } else { acc + 1 });
-        mismatches
-    }
-
-    fn match_columns(&self, first: usize, second: usize) -> bool {
-        assert!(
-            first < self.field[0].len() && second < self.field[0].len(),
-            "Columns to check out of bounds, the len was {} and the rows were {first} and {second}.",
-            self.field.len()
-        );
-        self.field.iter().all(|row| row[first] == row[second])
-    }
-
-    fn match_columns_with_smudge(&self, first: usize, second: usize) -> usize {
-        assert!(
-            first < self.field[0].len() && second < self.field[0].len(),
-            "Columns to check out of bounds, the len was {} and the rows were {first} and {second}.",
-            self.field.len()
-        );
-        self.field
-            .iter()
-            .map(|row| if row[first] == row[second] { 0 } else { 1 })
-            .sum::<usize>()
-    }
+/// (rows above the mirror, mismatching cells) for every mirror between two rows with exactly
+/// `smudges` mismatches
+fn mirrors_between_rows(field: &[Vec<Location>], smudges: usize) -> Vec<(usize, Vec<Mismatch>)> {
+    (1..field.len())
+        .filter_map(|rows| {
+            let mut mismatches = vec![];
+            // pair up the rows going outwards from the mirror until one side runs out
+            for (above, below) in (0..rows).rev().zip(rows..field.len()) {
+                for (x, (a, b)) in field[above].iter().zip(field[below].iter()).enumerate() {
+                    if a != b {
+                        if mismatches.len() == smudges {
+                            return None;
+                        }
+                        mismatches.push([(x, above), (x, below)]);
+                    }
+                }
+            }
+            (mismatches.len() == smudges).then_some((rows, mismatches))
+        })
+        .collect()
 }
 
 impl From<Vec<Vec<Location>>> for Pattern {
@@ -289,4 +211,49 @@ mod tests {
         let result = process_part2(input);
         assert_eq!(result, "400");
     }
+
+    #[test]
+    fn reflections() {
+        let input = "#.##..##.
+..#.##.#.
+##......#
+##......#
+..#.##.#.
+..##..##.
+#.#.##.#.
+
+#...##..#
+#....#..#
+..##..###
+#####.##.
+#####.##.
+..##..###
+#....#..#";
+        assert_eq!(
+            find_reflections(input, 0),
+            vec![
+                vec![Reflection {
+                    mirror: Mirror::Vertical(5),
+                    mismatches: vec![]
+                }],
+                vec![Reflection {
+                    mirror: Mirror::Horizontal(4),
+                    mismatches: vec![]
+                }]
+            ]
+        );
+        assert_eq!(
+            find_reflections(input, 1),
+            vec![
+                vec![Reflection {
+                    mirror: Mirror::Horizontal(3),
+                    mismatches: vec![[(0, 0), (0, 5)]]
+                }],
+                vec![Reflection {
+                    mirror: Mirror::Horizontal(1),
+                    mismatches: vec![[(4, 0), (4, 1)]]
+                }]
+            ]
+        );
+    }
 }
@@ -0,0 +1,53 @@
+use answer::{spin_load, Representation};
+use std::{fs, time::Instant};
+
+const CYCLES: usize = 1_000_000_000;
+
+/// Square platform with about a quarter rounded rocks and an eighth cube shaped rocks
+fn generate_platform(size: usize, mut seed: u64) -> String {
+    (0..size)
+        .map(|_| {
+            (0..size)
+                .map(|_| {
+                    seed = seed
+                        .wrapping_mul(6364136223846793005)
+                        .wrapping_add(1442695040888963407);
+                    match seed >> 61 {
+                        0..=1 => 'O',
+                        2 => '#',
+                        _ => '.',
+                    }
+                })
+                .collect::<String>()
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+fn main() {
+    let file = fs::read_to_string("./input.txt").unwrap();
+    let platforms = [("input".to_string(), file)]
+        .into_iter()
+        .chain([200, 300].map(|size| {
+            (
+                format!("{size}x{size}"),
+                generate_platform(size, size as u64),
+            )
+        }));
+    for (name, platform) in platforms {
+        let mut loads = vec![];
+        for representation in [Representation::Grid, Representation::Bitboard] {
+            let start = Instant::now();
+            let load = spin_load(&platform, CYCLES, representation);
+            println!(
+                "{name} {representation:?}: load {load} - {:?}",
+                start.elapsed()
+            );
+            loads.push(load);
+        }
+        assert_eq!(
+            loads[0], loads[1],
+            "Both representations should end up the same"
+        );
+    }
+}
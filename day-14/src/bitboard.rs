@@ -0,0 +1,285 @@
+//! The platform as one bit per space, so the rocks of a whole row roll at once
+use std::{
+    hash::{Hash, Hasher},
+    ops::Range,
+    rc::Rc,
+};
+
+use super::{Field, Space};
+
+const BITS: usize = u64::BITS as usize;
+
+/// Everything that doesn't move
+#[derive(Debug)]
+struct Layout {
+    width: usize,
+    height: usize,
+    /// Number of words for every row
+    words: usize,
+    cubes: Vec<u64>,
+    /// The stretches between the cube shaped rocks of every row
+    segments: Vec<Vec<Range<usize>>>,
+}
+
+/// Boards are only equal when they share the same layout, so comparing and hashing them only
+/// needs to look at the rounded rocks
+#[derive(Debug, Clone)]
+pub(crate) struct Bitboard {
+    layout: Rc<Layout>,
+    /// Bit x % 64 of word y * words + x / 64 is set for a rounded rock at (x, y), same for cubes
+    rounded: Vec<u64>,
+}
+
+impl PartialEq for Bitboard {
+    fn eq(&self, other: &Self) -> bool {
+        Rc::ptr_eq(&self.layout, &other.layout) && self.rounded == other.rounded
+    }
+}
+
+impl Eq for Bitboard {}
+
+impl Hash for Bitboard {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.rounded.hash(state);
+    }
+}
+
+impl Bitboard {
+    pub(crate) fn cycle(&mut self) {
+        self.tilt_north();
+        self.tilt_west();
+        self.tilt_south();
+        self.tilt_east();
+    }
+
+    pub(crate) fn north_load(&self) -> usize {
+        let Layout { height, words, .. } = *self.layout;
+        self.rounded
+            .chunks(words)
+            .enumerate()
+            .map(|(y, row)| {
+                row.iter().map(|w| w.count_ones() as usize).sum::<usize>() * (height - y)
+            })
+            .sum()
+    }
+
+    fn tilt_north(&mut self) {
+        let words = self.layout.words;
+        for y in 1..self.layout.height {
+            for word in 0..words {
+                self.roll_up(y, word);
+            }
+        }
+    }
+
+    fn tilt_south(&mut self) {
+        let words = self.layout.words;
+        for y in (0..self.layout.height - 1).rev() {
+            for word in 0..words {
+                self.roll_down(y, word);
+            }
+        }
+    }
+
+    /// Moves the rocks of one word of a row up as far as they go, all rows above have to be
+    /// tilted already
+    fn roll_up(&mut self, y: usize, word: usize) {
+        let words = self.layout.words;
+        let mut moving = self.rounded[y * words + word];
+        for y in (1..=y).rev() {
+            let (here, above) = (y * words + word, (y - 1) * words + word);
+            moving &= !(self.rounded[above] | self.layout.cubes[above]);
+            if moving == 0 {
+                break;
+            }
+            self.rounded[here] &= !moving;
+            self.rounded[above] |= moving;
+        }
+    }
+
+    fn roll_down(&mut self, y: usize, word: usize) {
+        let words = self.layout.words;
+        let mut moving = self.rounded[y * words + word];
+        for y in y..self.layout.height - 1 {
+            let (here, below) = (y * words + word, (y + 1) * words + word);
+            moving &= !(self.rounded[below] | self.layout.cubes[below]);
+            if moving == 0 {
+                break;
+            }
+            self.rounded[here] &= !moving;
+            self.rounded[below] |= moving;
+        }
+    }
+
+    /// Every segment of a row ends up with its rocks packed together at one end
+    fn tilt_west(&mut self) {
+        let layout = Rc::clone(&self.layout);
+        for (row, segments) in self.rounded.chunks_mut(layout.words).zip(&layout.segments) {
+            for segment in segments {
+                let rocks = count(row, segment);
+                set(row, segment, false);
+                set(row, &(segment.start..segment.start + rocks), true);
+            }
+        }
+    }
+
+    fn tilt_east(&mut self) {
+        let layout = Rc::clone(&self.layout);
+        for (row, segments) in self.rounded.chunks_mut(layout.words).zip(&layout.segments) {
+            for segment in segments {
+                let rocks = count(row, segment);
+                set(row, segment, false);
+                set(row, &(segment.end - rocks..segment.end), true);
+            }
+        }
+    }
+}
+
+/// The bits of the range that are in the word
+fn mask(range: &Range<usize>, word: usize) -> u64 {
+    let low = range.start.clamp(word * BITS, (word + 1) * BITS) - word * BITS;
+    let high = range.end.clamp(word * BITS, (word + 1) * BITS) - word * BITS;
+    let below = |bit: usize| if bit == BITS { !0 } else { (1 << bit) - 1 };
+    below(high) & !below(low)
+}
+
+fn words_of(range: &Range<usize>) -> Range<usize> {
+    range.start / BITS..range.end.div_ceil(BITS)
+}
+
+fn count(row: &[u64], range: &Range<usize>) -> usize {
+    words_of(range)
+        .map(|word| (row[word] & mask(range, word)).count_ones() as usize)
+        .sum()
+}
+
+fn set(row: &mut [u64], range: &Range<usize>, on: bool) {
+    for word in words_of(range) {
+        if on {
+            row[word] |= mask(range, word);
+        } else {
+            row[word] &= !mask(range, word);
+        }
+    }
+}
+
+impl From<&Field> for Bitboard {
+    fn from(field: &Field) -> Self {
+        let height = field.len();
+        let width = field.first().expect("Platform must have dimensions").len();
+        let words = width.div_ceil(BITS);
+        let mut cubes = vec![0; height * words];
+        let mut rounded = vec![0; height * words];
+        let mut segments = vec![];
+        for (y, row) in field.iter().enumerate() {
+            let mut row_segments = vec![];
+            let mut start = 0;
+            for (x, space) in row.iter().enumerate() {
+                let bit = 1 << (x % BITS);
+                match space {
+                    Space::RoundedRock => rounded[y * words + x / BITS] |= bit,
+                    Space::CubeShapedRock => {
+                        cubes[y * words + x / BITS] |= bit;
+                        if start < x {
+                            row_segments.push(start..x);
+                        }
+                        start = x + 1;
+                    }
+                    Space::Empty => {}
+                }
+            }
+            if start < width {
+                row_segments.push(start..width);
+            }
+            segments.push(row_segments);
+        }
+        let layout = Layout {
+            width,
+            height,
+            words,
+            cubes,
+            segments,
+        };
+        Self {
+            layout: Rc::new(layout),
+            rounded,
+        }
+    }
+}
+
+impl From<&Bitboard> for Field {
+    fn from(bitboard: &Bitboard) -> Self {
+        let Layout {
+            width,
+            height,
+            words,
+            ref cubes,
+            ..
+        } = *bitboard.layout;
+        (0..height)
+            .map(|y| {
+                (0..width)
+                    .map(|x| {
+                        let (word, bit) = (y * words + x / BITS, 1 << (x % BITS));
+                        if bitboard.rounded[word] & bit != 0 {
+                            Space::RoundedRock
+                        } else if cubes[word] & bit != 0 {
+                            Space::CubeShapedRock
+                        } else {
+                            Space::Empty
+                        }
+                    })
+                    .collect()
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{cycle, north_load, parse_input};
+
+    #[test]
+    fn same_as_field() {
+        let example = "O....#....
+O.OO#....#
+.....##...
+OO.#O....O
+.O.....O#.
+O.#..O.#.#
+..O..#O..O
+.......O..
+#....###..
+#OO..#....";
+        // wider than a word, so rocks have to roll from one word into the next
+        let mut seed = 14_u64;
+        let generated: String = (0..90)
+            .map(|_| {
+                (0..150)
+                    .map(|_| {
+                        seed = seed.wrapping_mul(6364136223846793005).wrapping_add(1);
+                        match seed >> 60 {
+                            0..=3 => 'O',
+                            4..=5 => '#',
+                            _ => '.',
+                        }
+                    })
+                    .collect::<String>()
+            })
+            .collect::<Vec<_>>()
+            .join("\n");
+
+        for input in [example, &generated] {
+            let mut field = parse_input(input).unwrap().1;
+            let mut bitboard = Bitboard::from(&field);
+            assert_eq!(Field::from(&bitboard), field);
+            for _ in 0..20 {
+                cycle(&mut field);
+                bitboard.cycle();
+                assert_eq!(Field::from(&bitboard), field);
+                assert_eq!(bitboard.north_load(), north_load(&field));
+            }
+        }
+    }
+}
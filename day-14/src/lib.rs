@@ -1,50 +1,57 @@
-use std::collections::HashMap;
-
+use bitboard::Bitboard;
+use cycles::{state_at, Method};
 use nom::{
     character::complete::{newline, one_of},
     multi::{many1, separated_list1},
     IResult,
 };
 
+mod bitboard;
+
 pub fn process_part1(input: &str) -> String {
     let (input, mut platform) = parse_input(input).unwrap();
     assert!(input.is_empty());
     tilt_north(&mut platform);
-    platform
-        .iter()
-        .rev()
-        .enumerate()
-        .map(|(row_i, row)| {
-            row.iter()
-                .filter(|space| space == &&Space::RoundedRock)
-                .count()
-                * (row_i + 1)
-        })
-        .sum::<usize>()
-        .to_string()
+    north_load(&platform).to_string()
 }
 
 pub fn process_part2(input: &str) -> String {
-    let (input, mut platform) = parse_input(input).unwrap();
+    spin_load(input, 1_000_000_000, Representation::Bitboard).to_string()
+}
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum Representation {
+    /// Every space on its own, rocks roll one space at a time
+    Grid,
+    /// One bit for every space
+    Bitboard,
+}
+
+/// Load on the north support beams after the given number of spin cycles
+pub fn spin_load(input: &str, cycles: usize, representation: Representation) -> usize {
+    let (input, platform) = parse_input(input).unwrap();
     assert!(input.is_empty());
-    let target = 1_000_000_000;
-    let mut map = HashMap::new();
-    let mut loop_length = 0;
-    let mut loops_ran = 0;
-    for i in 1_usize..target {
-        if let Some(&step) = map.get(&platform) {
-            loop_length = i - step;
-            loops_ran = i;
-            break;
+    match representation {
+        Representation::Grid => {
+            let spin = |platform: &Field| {
+                let mut platform = platform.clone();
+                cycle(&mut platform);
+                platform
+            };
+            north_load(&state_at(&platform, spin, cycles, Method::Hash))
+        }
+        Representation::Bitboard => {
+            let spin = |platform: &Bitboard| {
+                let mut platform = platform.clone();
+                platform.cycle();
+                platform
+            };
+            state_at(&Bitboard::from(&platform), spin, cycles, Method::Hash).north_load()
         }
-        map.insert(platform.clone(), i);
-        cycle(&mut platform);
-    }
-    let remaining = target - loops_ran;
-    let cycles_left = remaining % loop_length;
-    for _ in 0..(cycles_left + 1) {
-        cycle(&mut platform);
     }
+}
+
+fn north_load(platform: &Field) -> usize {
     platform
         .iter()
         .rev()
@@ -56,7 +63,6 @@ pub fn process_part2(input: &str) -> String {
                 * (row_i + 1)
         })
         .sum::<usize>()
-        .to_string()
 }
 
 #[derive(Debug, PartialEq, Eq, Hash, Clone, Copy)]
@@ -186,12 +192,12 @@ fn roll_west(platform: &mut Field, x: usize, y: usize) {
 
 type Line<'a> = Vec<Space>;
 
-fn parse_input(input: &str) -> IResult<&str, Vec<Line>> {
+fn parse_input(input: &str) -> IResult<&str, Vec<Line<'_>>> {
     let (input, lines) = separated_list1(newline, parse_line)(input)?;
     Ok((input, lines))
 }
 
-fn parse_line(input: &str) -> IResult<&str, Line> {
+fn parse_line(input: &str) -> IResult<&str, Line<'_>> {
     let (input, line) = many1(parse_space)(input)?;
     Ok((input, line))
 }
@@ -0,0 +1,156 @@
+//! The contraption as a graph of the splitters that split beams. The tiles energised from a
+//! splitter are worked out once, so every entry beam only has to be followed to the first one.
+use std::collections::{HashMap, HashSet};
+
+use graph::Graph;
+
+use super::{Beam, Contraption, Direction, Tile};
+
+/// One bit for every tile of the contraption
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct TileSet {
+    bits: Vec<u64>,
+}
+
+impl TileSet {
+    fn new(tiles: usize) -> Self {
+        Self {
+            bits: vec![0; tiles.div_ceil(64)],
+        }
+    }
+
+    fn insert(&mut self, tile: usize) {
+        self.bits[tile / 64] |= 1 << (tile % 64);
+    }
+
+    fn union_with(&mut self, other: &Self) {
+        self.bits
+            .iter_mut()
+            .zip(other.bits.iter())
+            .for_each(|(a, b)| *a |= b);
+    }
+
+    pub(crate) fn contains(&self, tile: usize) -> bool {
+        self.bits[tile / 64] & (1 << (tile % 64)) != 0
+    }
+
+    pub(crate) fn len(&self) -> usize {
+        self.bits.iter().map(|b| b.count_ones() as usize).sum()
+    }
+}
+
+/// The tiles a beam passes until it gets split or leaves the contraption
+#[derive(Debug)]
+struct Segment {
+    tiles: Vec<usize>,
+    /// The splitter that ends it
+    splitter: Option<(usize, usize)>,
+}
+
+#[derive(Debug)]
+pub(crate) struct BeamGraph<'a> {
+    contraption: &'a Contraption,
+    /// Index into energised for every splitter
+    component_of: HashMap<(usize, usize), usize>,
+    /// Everything that gets energised once a beam is split, for every strongly connected component
+    energised: Vec<TileSet>,
+}
+
+impl<'a> BeamGraph<'a> {
+    pub(crate) fn new(contraption: &'a Contraption) -> Self {
+        let mut graph = Graph::new();
+        let mut segments = HashMap::new();
+        for (y, row) in contraption.grid.iter().enumerate() {
+            for (x, tile) in row.iter().enumerate() {
+                // the beams the splitter sends out when it gets hit on its flat side
+                let split: Vec<Beam> = match tile {
+                    Tile::HorizontalSplitter => Beam::new(Direction::North, x, y).next(contraption),
+                    Tile::VerticalSplitter => Beam::new(Direction::East, x, y).next(contraption),
+                    _ => continue,
+                }
+                .into_iter()
+                .flatten()
+                .collect();
+                graph.add_node((x, y));
+                let from_splitter: Vec<Segment> = split
+                    .into_iter()
+                    .map(|beam| Segment::follow(beam, contraption))
+                    .collect();
+                for segment in from_splitter.iter() {
+                    if let Some(splitter) = segment.splitter {
+                        graph.add_edge((x, y), splitter, 0);
+                    }
+                }
+                segments.insert((x, y), from_splitter);
+            }
+        }
+
+        // the components a component has edges to come first, so their tiles are known already
+        let mut component_of = HashMap::new();
+        let mut energised = Vec::new();
+        for (index, component) in graph
+            .strongly_connected_components()
+            .into_iter()
+            .enumerate()
+        {
+            let mut tiles = TileSet::new(contraption.width() * contraption.height());
+            for splitter in component.iter() {
+                component_of.insert(*splitter, index);
+            }
+            for splitter in component.iter() {
+                for segment in segments[splitter].iter() {
+                    segment.tiles.iter().for_each(|&tile| tiles.insert(tile));
+                    if let Some(next) = segment.splitter {
+                        if component_of[&next] != index {
+                            tiles.union_with(&energised[component_of[&next]]);
+                        }
+                    }
+                }
+            }
+            energised.push(tiles);
+        }
+
+        Self {
+            contraption,
+            component_of,
+            energised,
+        }
+    }
+
+    /// Every tile the beam passes, as y * width + x
+    pub(crate) fn energised_from(&self, beam: Beam) -> TileSet {
+        let segment = Segment::follow(beam, self.contraption);
+        let mut tiles = match segment.splitter {
+            Some(splitter) => self.energised[self.component_of[&splitter]].clone(),
+            None => TileSet::new(self.contraption.width() * self.contraption.height()),
+        };
+        segment.tiles.iter().for_each(|&tile| tiles.insert(tile));
+        tiles
+    }
+}
+
+impl Segment {
+    fn follow(mut beam: Beam, contraption: &Contraption) -> Self {
+        let mut tiles = vec![];
+        // without splitting, a beam can still come back through the splitter it came from
+        let mut seen = HashSet::from([beam]);
+        loop {
+            if beam.splits(contraption) {
+                return Self {
+                    tiles,
+                    splitter: Some((beam.x, beam.y)),
+                };
+            }
+            tiles.push(beam.y * contraption.width() + beam.x);
+            match beam.next(contraption) {
+                [Some(next), None] if seen.insert(next) => beam = next,
+                _ => {
+                    return Self {
+                        tiles,
+                        splitter: None,
+                    }
+                }
+            }
+        }
+    }
+}
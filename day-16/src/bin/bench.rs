@@ -0,0 +1,22 @@
+use answer::{energised_per_entry, Method};
+use std::{fs, time::Instant};
+
+fn main() {
+    let file = fs::read_to_string("./input.txt").unwrap();
+    let mut results = vec![];
+    for method in [Method::Bounce, Method::BeamGraph] {
+        let start = Instant::now();
+        let energised = energised_per_entry(&file, method);
+        let time = start.elapsed();
+        println!(
+            "{method:?}: {} entries, at most {} energised - {time:?}",
+            energised.len(),
+            energised.iter().max().unwrap()
+        );
+        results.push(energised);
+    }
+    assert_eq!(
+        results[0], results[1],
+        "Both methods should energise the same tiles"
+    );
+}
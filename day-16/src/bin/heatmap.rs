@@ -0,0 +1,27 @@
+use answer::heatmap::{Entry, Heatmap, View};
+use std::{env, fs};
+
+/// Prints the contraption in the terminal, `heatmap [entries|beam|best]`
+fn main() {
+    let file = fs::read_to_string("./input.txt").unwrap();
+    let (entry, view) = match env::args().nth(1).as_deref() {
+        None | Some("entries") => (Entry::TopLeft, View::Entries),
+        Some("beam") => (Entry::TopLeft, View::Beam),
+        Some("best") => (Entry::Best, View::Beam),
+        Some(mode) => panic!("Unknown mode: {mode}"),
+    };
+    let heatmap = Heatmap::new(&file, entry);
+    print!("{}", heatmap.render_ansi(view));
+    match view {
+        View::Entries => println!("At most {} entry beams per tile", heatmap.most_entries()),
+        View::Beam => println!(
+            "{} tiles energised",
+            heatmap
+                .tiles
+                .iter()
+                .flatten()
+                .filter(|t| t.is_energised())
+                .count()
+        ),
+    }
+}
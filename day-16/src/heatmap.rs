@@ -0,0 +1,208 @@
+//! What the beams do on every tile, with pictures of it for the terminal
+use super::{beam_graph::BeamGraph, entry_beams, parse_input, Beam, Beams};
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum Entry {
+    /// The beam from part 1, going east from the top left corner
+    TopLeft,
+    /// The entry beam that energises the most tiles, the first one if there are more
+    Best,
+}
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum View {
+    /// Every tile coloured by how many entry beams energise it
+    Entries,
+    /// The chosen entry beam drawn onto the layout
+    Beam,
+}
+
+#[derive(Debug, Default, PartialEq, Eq, Clone, Copy)]
+pub struct TileHeat {
+    /// Whether the chosen beam went north, south, east and west through the tile
+    pub directions: [bool; 4],
+    /// Number of entry beams that energise the tile
+    pub entries: usize,
+}
+
+impl TileHeat {
+    pub fn is_energised(&self) -> bool {
+        self.directions.contains(&true)
+    }
+}
+
+#[derive(Debug)]
+pub struct Heatmap {
+    /// The mirrors and splitters as they are in the input
+    pub layout: Vec<Vec<char>>,
+    pub tiles: Vec<Vec<TileHeat>>,
+}
+
+impl Heatmap {
+    pub fn new(input: &str, entry: Entry) -> Self {
+        let (rest, mut contraption) = parse_input(input).unwrap();
+        assert!(rest.is_empty());
+        let width = contraption.width();
+        let mut tiles = vec![vec![TileHeat::default(); width]; contraption.height()];
+
+        let mut best = (0, Beam::default());
+        let beam_graph = BeamGraph::new(&contraption);
+        for beam in entry_beams(&contraption) {
+            let energised = beam_graph.energised_from(beam);
+            for (y, row) in tiles.iter_mut().enumerate() {
+                for (x, tile) in row.iter_mut().enumerate() {
+                    if energised.contains(y * width + x) {
+                        tile.entries += 1;
+                    }
+                }
+            }
+            if energised.len() > best.0 {
+                best = (energised.len(), beam);
+            }
+        }
+
+        let start = match entry {
+            Entry::TopLeft => Beam::default(),
+            Entry::Best => best.1,
+        };
+        let mut beams = Beams::new(start);
+        beams.bounce(&mut contraption);
+        for beam in beams.cache.iter().chain([&start]) {
+            tiles[beam.y][beam.x].directions[beam.direction as usize] = true;
+        }
+
+        let layout = input.lines().map(|line| line.chars().collect()).collect();
+        Self { layout, tiles }
+    }
+
+    pub fn most_entries(&self) -> usize {
+        self.tiles
+            .iter()
+            .flat_map(|row| row.iter().map(|tile| tile.entries))
+            .max()
+            .unwrap_or(0)
+    }
+
+    /// The layout with the beam on the empty tiles: an arrow if it went one way, otherwise the
+    /// number of directions
+    pub fn beam_diagram(&self) -> Vec<String> {
+        self.layout
+            .iter()
+            .zip(self.tiles.iter())
+            .map(|(layout, tiles)| {
+                layout
+                    .iter()
+                    .zip(tiles.iter())
+                    .map(|(&c, tile)| beam_symbol(c, tile))
+                    .collect()
+            })
+            .collect()
+    }
+
+    pub fn render_ansi(&self, view: View) -> String {
+        let most = self.most_entries().max(1);
+        let mut output = String::new();
+        for (layout, tiles) in self.layout.iter().zip(self.tiles.iter()) {
+            for (&c, tile) in layout.iter().zip(tiles.iter()) {
+                match view {
+                    View::Entries => {
+                        let (r, g, b) = heat_colour(tile.entries as f64 / most as f64);
+                        // dark text on the bright end of the scale
+                        let text = if r + g + b > 400 { 30 } else { 37 };
+                        output.push_str(&format!("\x1b[{text};48;2;{r};{g};{b}m{c}"));
+                    }
+                    View::Beam if tile.is_energised() => {
+                        output.push_str(&format!("\x1b[1;33m{}", beam_symbol(c, tile)))
+                    }
+                    View::Beam => output.push_str(&format!("\x1b[2m{c}")),
+                }
+                output.push_str("\x1b[0m");
+            }
+            output.push('\n');
+        }
+        output
+    }
+}
+
+fn beam_symbol(c: char, tile: &TileHeat) -> char {
+    if c != '.' {
+        return c;
+    }
+    match tile.directions {
+        [false, false, false, false] => '.',
+        [true, false, false, false] => '^',
+        [false, true, false, false] => 'v',
+        [false, false, true, false] => '>',
+        [false, false, false, true] => '<',
+        directions => {
+            let count = directions.iter().filter(|&&d| d).count() as u32;
+            char::from_digit(count, 10).unwrap()
+        }
+    }
+}
+
+/// Black through red and yellow to white for heat from 0 to 1
+fn heat_colour(heat: f64) -> (u32, u32, u32) {
+    let channel = |offset: f64| ((3.0 * heat - offset).clamp(0.0, 1.0) * 255.0).round() as u32;
+    (channel(0.0), channel(1.0), channel(2.0))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const EXAMPLE: &str = r".|...\....
+|.-.\.....
+.....|-...
+........|.
+..........
+.........\
+..../.\\..
+.-.-/..|..
+.|....-|.\
+..//.|....";
+
+    #[test]
+    fn beam_diagram() {
+        let heatmap = Heatmap::new(EXAMPLE, Entry::TopLeft);
+        assert_eq!(
+            heatmap.beam_diagram(),
+            vec![
+                r">|<<<\....",
+                r"|v-.\^....",
+                r".v...|->>>",
+                r".v...v^.|.",
+                r".v...v^...",
+                r".v...v^..\",
+                r".v../2\\..",
+                r"<->-/vv|..",
+                r".|<<<2-|.\",
+                r".v//.|.v..",
+            ]
+        );
+        let energised = heatmap.tiles.iter().flatten().filter(|t| t.is_energised());
+        assert_eq!(energised.count(), 46);
+
+        let best = Heatmap::new(EXAMPLE, Entry::Best);
+        let energised = best.tiles.iter().flatten().filter(|t| t.is_energised());
+        assert_eq!(energised.count(), 51);
+        // the best beam goes south into the fourth column
+        assert!(best.tiles[0][3].directions[1]);
+    }
+
+    #[test]
+    fn entries() {
+        let heatmap = Heatmap::new(EXAMPLE, Entry::TopLeft);
+        // the end of the row that most beams get split onto
+        assert_eq!(heatmap.most_entries(), 24);
+        assert_eq!(heatmap.tiles[7][0].entries, 24);
+        assert_eq!(heatmap.tiles[9][9].entries, 2);
+        assert!(heatmap.tiles.iter().flatten().all(|t| t.entries > 0));
+        assert_eq!(heat_colour(0.0), (0, 0, 0));
+        assert_eq!(heat_colour(0.5), (255, 128, 0));
+        assert_eq!(heat_colour(1.0), (255, 255, 255));
+        let ansi = heatmap.render_ansi(View::Entries);
+        assert_eq!(ansi.lines().count(), 10);
+        assert!(ansi.starts_with("\x1b[37;48;2;"));
+    }
+}
@@ -1,11 +1,15 @@
 use std::collections::HashSet;
 
+use beam_graph::BeamGraph;
 use nom::{
     character::complete::{newline, one_of},
     multi::{many1, separated_list1},
     IResult,
 };
 
+mod beam_graph;
+pub mod heatmap;
+
 pub fn process_part1(input: &str) -> String {
     let (input, mut contraption) = parse_input(input).unwrap();
     assert!(input.is_empty());
@@ -15,34 +19,61 @@ pub fn process_part1(input: &str) -> String {
 }
 
 pub fn process_part2(input: &str) -> String {
+    energised_per_entry(input, Method::BeamGraph)
+        .into_iter()
+        .max()
+        .expect("Should be a biggest one!")
+        .to_string()
+}
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum Method {
+    /// Follow every beam from scratch for every entry
+    Bounce,
+    /// Follow every beam between splitters once, and combine the results
+    BeamGraph,
+}
+
+/// Number of energised tiles for every beam that can enter the contraption
+pub fn energised_per_entry(input: &str, method: Method) -> Vec<usize> {
     let (input, mut contraption) = parse_input(input).unwrap();
     assert!(input.is_empty());
-    let beamses: Vec<Beams> = (0..contraption.width())
+    let entries = entry_beams(&contraption);
+    match method {
+        Method::Bounce => entries
+            .into_iter()
+            .map(|beam| {
+                contraption.reset();
+                Beams::new(beam).bounce(&mut contraption);
+                contraption.count_energised()
+            })
+            .collect(),
+        Method::BeamGraph => {
+            let beam_graph = BeamGraph::new(&contraption);
+            entries
+                .into_iter()
+                .map(|beam| beam_graph.energised_from(beam).len())
+                .collect()
+        }
+    }
+}
+
+/// Every beam that comes in from the edge
+fn entry_beams(contraption: &Contraption) -> Vec<Beam> {
+    (0..contraption.width())
         .flat_map(|x| {
             [
-                Beams::new(Beam::new(Direction::South, x, 0)),
-                Beams::new(Beam::new(Direction::North, x, contraption.height() - 1)),
+                Beam::new(Direction::South, x, 0),
+                Beam::new(Direction::North, x, contraption.height() - 1),
             ]
-            .into_iter()
         })
         .chain((0..contraption.height()).flat_map(|y| {
             [
-                Beams::new(Beam::new(Direction::East, 0, y)),
-                Beams::new(Beam::new(Direction::West, contraption.width() - 1, y)),
+                Beam::new(Direction::East, 0, y),
+                Beam::new(Direction::West, contraption.width() - 1, y),
             ]
-            .into_iter()
         }))
-        .collect();
-    beamses
-        .into_iter()
-        .map(|mut beams| {
-            contraption.reset();
-            beams.bounce(&mut contraption);
-            contraption.count_energised()
-        })
-        .max()
-        .expect("Should be a biggest one!")
-        .to_string()
+        .collect()
 }
 
 #[derive(Debug, PartialEq, Eq, Clone, Copy, Hash)]
@@ -120,9 +151,25 @@ impl Beam {
     }
 
     fn step(self, contraption: &mut Contraption) -> [Option<Beam>; 2] {
+        contraption.energised[self.y][self.x] = true;
+        self.next(contraption)
+    }
+
+    /// Whether the beam hits the flat side of a splitter
+    fn splits(&self, contraption: &Contraption) -> bool {
+        match contraption.grid[self.y][self.x] {
+            Tile::HorizontalSplitter => {
+                matches!(self.direction, Direction::North | Direction::South)
+            }
+            Tile::VerticalSplitter => matches!(self.direction, Direction::East | Direction::West),
+            _ => false,
+        }
+    }
+
+    /// Where the beam goes from this tile
+    fn next(self, contraption: &Contraption) -> [Option<Beam>; 2] {
         let (x, y) = (self.x, self.y);
         let direction = self.direction;
-        contraption.energised[y][x] = true;
         let tile = contraption.grid[y][x];
         match tile {
             Tile::PositiveMirror => match direction {
@@ -280,7 +327,7 @@ fn parse_input(input: &str) -> IResult<&str, Contraption> {
     Ok((input, contraption.into()))
 }
 
-fn parse_line(input: &str) -> IResult<&str, Line> {
+fn parse_line(input: &str) -> IResult<&str, Line<'_>> {
     let (input, line) = many1(parse_tile)(input)?;
     Ok((input, line))
 }
@@ -325,4 +372,31 @@ mod tests {
         let result = process_part2(input);
         assert_eq!(result, "51");
     }
+
+    #[test]
+    fn methods_agree() {
+        let input = r".|...\....
+|.-.\.....
+.....|-...
+........|.
+..........
+.........\
+..../.\\..
+.-.-/..|..
+.|....-|.\
+..//.|....";
+        let bounced = energised_per_entry(input, Method::Bounce);
+        assert_eq!(bounced.len(), 40);
+        assert_eq!(energised_per_entry(input, Method::BeamGraph), bounced);
+        // beams going round in circles through the splitters they came from
+        let input = r"/..\.
+.....
+|.\..
+.....
+\../.";
+        assert_eq!(
+            energised_per_entry(input, Method::BeamGraph),
+            energised_per_entry(input, Method::Bounce)
+        );
+    }
 }
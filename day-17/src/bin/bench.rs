@@ -0,0 +1,32 @@
+use answer::{heat_loss_baseline, process_part1, process_part2, Rules};
+use std::{fs, time::Instant};
+
+const RUNS: u32 = 10;
+
+fn main() {
+    let file = fs::read_to_string("./input.txt").unwrap();
+    for (rules, process) in [
+        (Rules::Normal, process_part1 as fn(&str) -> String),
+        (Rules::Ultra, process_part2),
+    ] {
+        let start = Instant::now();
+        let mut baseline = 0;
+        for _ in 0..RUNS {
+            baseline =
+                heat_loss_baseline(&file, rules).expect("There's always a way through the city");
+        }
+        let baseline_time = start.elapsed() / RUNS;
+
+        let start = Instant::now();
+        let mut dense = String::new();
+        for _ in 0..RUNS {
+            dense = process(&file);
+        }
+        let dense_time = start.elapsed() / RUNS;
+
+        assert_eq!(baseline.to_string(), dense);
+        println!(
+            "{rules:?}: {dense} - baseline HashMap and heap: {baseline_time:?}, dense array and buckets: {dense_time:?}"
+        );
+    }
+}
@@ -0,0 +1,12 @@
+use answer::{render_path, render_svg, Rules};
+use std::fs;
+
+fn main() {
+    let file = fs::read_to_string("./input.txt").unwrap();
+    for (rules, name) in [(Rules::Normal, "part-1"), (Rules::Ultra, "part-2")] {
+        println!("{}\n", render_path(&file, rules));
+        let svg_name = format!("./{name}.svg");
+        fs::write(&svg_name, render_svg(&file, rules)).unwrap();
+        println!("Written {svg_name}\n");
+    }
+}
@@ -5,25 +5,112 @@ use nom::{
     multi::{many1, separated_list1},
     IResult,
 };
+use search::{Movement, Path, SearchError};
 
 pub fn process_part1(input: &str) -> String {
     let (input, city) = parse_input(input).unwrap();
     assert!(input.is_empty());
-    let (target_x, target_y) = (city[0].len() - 1, city.len() - 1);
-    let target = Coord::new(target_x, target_y);
-    let shortest = shortest_path(target, &city, neighbours::<1, 3>);
+    let shortest = least_heat_loss::<1, 3>(&city).expect("There's always a way through the city");
     shortest.to_string()
 }
 
 pub fn process_part2(input: &str) -> String {
     let (input, city) = parse_input(input).unwrap();
     assert!(input.is_empty());
-    let (target_x, target_y) = (city[0].len() - 1, city.len() - 1);
-    let target = Coord::new(target_x, target_y);
-    let shortest = shortest_path(target, &city, neighbours::<4, 10>);
+    let shortest = least_heat_loss::<4, 10>(&city).expect("There's always a way through the city");
     shortest.to_string()
 }
 
+/// Heat loss found with the HashMap and BinaryHeap Dijkstra that `process_part1` and
+/// `process_part2` used before, only there for the bench to compare against
+#[doc(hidden)]
+pub fn heat_loss_baseline(input: &str, rules: Rules) -> Option<usize> {
+    let (input, city) = parse_input(input).unwrap();
+    assert!(input.is_empty());
+    match rules {
+        Rules::Normal => baseline_heat_loss::<1, 3>(&city),
+        Rules::Ultra => baseline_heat_loss::<4, 10>(&city),
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Rules {
+    /// Regular crucibles go at most 3 blocks in a straight line
+    Normal,
+    /// Ultra crucibles go at least 4 and at most 10 blocks in a straight line
+    Ultra,
+}
+
+/// Returns the city with the optimal path drawn over it in arrows, like the puzzle description
+pub fn render_path(input: &str, rules: Rules) -> String {
+    let (input, city) = parse_input(input).unwrap();
+    assert!(input.is_empty());
+    let mut map: Vec<Vec<char>> = city
+        .iter()
+        .map(|row| row.iter().map(|&d| (b'0' + d) as char).collect())
+        .collect();
+    // the start block is never entered, so it keeps its digit
+    for node in optimal_path(&city, rules).states.iter().skip(1) {
+        let (x, y) = node.coord.tuple();
+        map[y][x] = node.direction.arrow();
+    }
+    map.into_iter()
+        .map(|row| row.into_iter().collect::<String>())
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// Returns an SVG of the city with blocks coloured by heat loss and the optimal path on top
+pub fn render_svg(input: &str, rules: Rules) -> String {
+    const BLOCK: usize = 10;
+    let (input, city) = parse_input(input).unwrap();
+    assert!(input.is_empty());
+    let path = optimal_path(&city, rules);
+    let (width, height) = (city[0].len() * BLOCK, city.len() * BLOCK);
+
+    let mut svg = format!(
+        "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{width}\" height=\"{height}\" viewBox=\"0 0 {width} {height}\">\n"
+    );
+    for (y, row) in city.iter().enumerate() {
+        for (x, &heat_loss) in row.iter().enumerate() {
+            // from pale yellow for 1 to dark red for 9
+            let t = (heat_loss - 1) as usize;
+            let (r, g, b) = (255 - t * 12, 230 - t * 27, 150 - t * 18);
+            svg.push_str(&format!(
+                "<rect x=\"{}\" y=\"{}\" width=\"{BLOCK}\" height=\"{BLOCK}\" fill=\"rgb({r},{g},{b})\"/>\n",
+                x * BLOCK,
+                y * BLOCK
+            ));
+        }
+    }
+    let points = path
+        .states
+        .iter()
+        .map(|node| {
+            let (x, y) = node.coord.tuple();
+            format!("{},{}", x * BLOCK + BLOCK / 2, y * BLOCK + BLOCK / 2)
+        })
+        .collect::<Vec<_>>()
+        .join(" ");
+    svg.push_str(&format!(
+        "<polyline points=\"{points}\" fill=\"none\" stroke=\"navy\" stroke-width=\"{}\"/>\n",
+        BLOCK / 3
+    ));
+    svg.push_str(&format!(
+        "<title>Heat loss: {}</title>\n</svg>\n",
+        path.cost
+    ));
+    svg
+}
+
+fn optimal_path(city: &[Line], rules: Rules) -> Path<Node> {
+    let shortest = match rules {
+        Rules::Normal => Crucible::<1, 3>::new(city).shortest_path(),
+        Rules::Ultra => Crucible::<4, 10>::new(city).shortest_path(),
+    };
+    shortest.expect("There's always a way through the city")
+}
+
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
 struct Coord {
     x: usize,
@@ -62,6 +149,15 @@ impl Direction {
             Self::West => Self::East,
         }
     }
+
+    fn arrow(&self) -> char {
+        match self {
+            Self::North => '^',
+            Self::South => 'v',
+            Self::East => '>',
+            Self::West => '<',
+        }
+    }
 }
 
 #[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
@@ -111,8 +207,8 @@ fn neighbours<const MIN_STEPS: usize, const MAX_STEPS: usize>(
 ) -> Vec<Node> {
     let (x, y) = node.coord.tuple();
     let directions_and_coords = [
-        (Direction::West, x > 0, Coord::new(x - 1, y)),
-        (Direction::North, y > 0, Coord::new(x, y - 1)),
+        (Direction::West, x > 0, Coord::new(x.wrapping_sub(1), y)),
+        (Direction::North, y > 0, Coord::new(x, y.wrapping_sub(1))),
         (Direction::East, x < width, Coord::new(x + 1, y)),
         (Direction::South, y < height, Coord::new(x, y + 1)),
     ];
@@ -135,12 +231,79 @@ fn neighbours<const MIN_STEPS: usize, const MAX_STEPS: usize>(
         .collect()
 }
 
-fn shortest_path<F>(target: Coord, city: &[Vec<u8>], neighbour_func: F) -> usize
-where
-    F: Fn(Node, usize, usize) -> Vec<Node>,
-{
+/// Dijkstra with the distances in a dense array indexed by state instead of a HashMap, and a
+/// bucket queue instead of a heap: every block costs 1 to 9, so 10 buckets cover all queued costs
+fn least_heat_loss<const MIN_STEPS: usize, const MAX_STEPS: usize>(city: &[Line]) -> Option<usize> {
+    const DIRECTIONS: [Direction; 4] = [
+        Direction::North,
+        Direction::South,
+        Direction::East,
+        Direction::West,
+    ];
+    let (height, width) = (city.len(), city[0].len());
+    let index = |x: usize, y: usize, direction: Direction, steps: usize| {
+        ((y * width + x) * DIRECTIONS.len() + direction as usize) * (MAX_STEPS + 1) + steps
+    };
+    let mut distances = vec![usize::MAX; height * width * DIRECTIONS.len() * (MAX_STEPS + 1)];
+    let mut buckets = vec![Vec::new(); 10];
+    for direction in [Direction::East, Direction::South] {
+        distances[index(0, 0, direction, 0)] = 0;
+        buckets[0].push((0, 0, direction, 0));
+    }
+    let mut queued = 2_usize;
+
+    let mut heat_loss = 0;
+    while queued > 0 {
+        let bucket = heat_loss % buckets.len();
+        while let Some((x, y, direction, steps)) = buckets[bucket].pop() {
+            queued -= 1;
+            if distances[index(x, y, direction, steps)] < heat_loss {
+                continue; // we've already found a cheaper way to get here
+            }
+            // the crucible needs MIN_STEPS to stop as well
+            if (x, y) == (width - 1, height - 1) && steps >= MIN_STEPS {
+                return Some(heat_loss);
+            }
+            for new_direction in DIRECTIONS {
+                let new_steps = if new_direction == direction {
+                    if steps >= MAX_STEPS {
+                        continue; // we cannot go straight after taking MAX_STEPS
+                    }
+                    steps + 1
+                } else {
+                    if new_direction == direction.opposite() || steps < MIN_STEPS {
+                        continue; // no turning back and no turning before taking MIN_STEPS
+                    }
+                    1
+                };
+                let (new_x, new_y) = match new_direction {
+                    Direction::North if y > 0 => (x, y - 1),
+                    Direction::South if y + 1 < height => (x, y + 1),
+                    Direction::East if x + 1 < width => (x + 1, y),
+                    Direction::West if x > 0 => (x - 1, y),
+                    _ => continue,
+                };
+                let new_heat_loss = heat_loss + city[new_y][new_x] as usize;
+                let new_index = index(new_x, new_y, new_direction, new_steps);
+                if new_heat_loss < distances[new_index] {
+                    distances[new_index] = new_heat_loss;
+                    buckets[new_heat_loss % 10].push((new_x, new_y, new_direction, new_steps));
+                    queued += 1;
+                }
+            }
+        }
+        heat_loss += 1;
+    }
+    None
+}
+
+/// Dijkstra with the distances in a HashMap and a BinaryHeap as the queue
+fn baseline_heat_loss<const MIN_STEPS: usize, const MAX_STEPS: usize>(
+    city: &[Line],
+) -> Option<usize> {
     let height = city.len() - 1;
     let width = city[0].len() - 1;
+    let target = Coord::new(width, height);
 
     let start_east = Node::new(Coord::start(), Direction::East, 0);
     let start_south = Node::new(Coord::start(), Direction::South, 0);
@@ -160,11 +323,12 @@ where
     });
 
     while let Some(Step { value, node }) = queue.pop() {
-        if node.coord == target {
-            return value;
+        // the crucible needs MIN_STEPS to stop as well
+        if node.coord == target && node.steps >= MIN_STEPS {
+            return Some(value);
         }
 
-        for neighbour in neighbour_func(node, height, width).into_iter() {
+        for neighbour in neighbours::<MIN_STEPS, MAX_STEPS>(node, height, width).into_iter() {
             let (x, y) = neighbour.coord.tuple();
             let new_value = value + city[y][x] as usize;
             if let Some(&shortest_so_far) = distances.get(&neighbour) {
@@ -180,7 +344,51 @@ where
         }
     }
 
-    unreachable!("Cannot find a path to the target!");
+    None
+}
+
+/// Movement rules for a crucible going from the top left to the bottom right of the city
+struct Crucible<'a, const MIN_STEPS: usize, const MAX_STEPS: usize> {
+    city: &'a [Vec<u8>],
+    target: Coord,
+}
+
+impl<'a, const MIN_STEPS: usize, const MAX_STEPS: usize> Crucible<'a, MIN_STEPS, MAX_STEPS> {
+    fn new(city: &'a [Vec<u8>]) -> Self {
+        let target = Coord::new(city[0].len() - 1, city.len() - 1);
+        Self { city, target }
+    }
+
+    fn shortest_path(&self) -> Result<Path<Node>, SearchError> {
+        let start_east = Node::new(Coord::start(), Direction::East, 0);
+        let start_south = Node::new(Coord::start(), Direction::South, 0);
+        search::shortest_path(self, [start_east, start_south], |node| {
+            // the crucible needs MIN_STEPS to stop as well
+            node.coord == self.target && node.steps >= MIN_STEPS
+        })
+    }
+}
+
+impl<const MIN_STEPS: usize, const MAX_STEPS: usize> Movement
+    for Crucible<'_, MIN_STEPS, MAX_STEPS>
+{
+    type State = Node;
+
+    fn neighbours(&self, node: &Node) -> Vec<(Node, usize)> {
+        let (height, width) = (self.target.y, self.target.x);
+        neighbours::<MIN_STEPS, MAX_STEPS>(node.clone(), height, width)
+            .into_iter()
+            .map(|neighbour| {
+                let (x, y) = neighbour.coord.tuple();
+                (neighbour, self.city[y][x] as usize)
+            })
+            .collect()
+    }
+
+    /// every block costs at least 1, so the Manhattan distance never overestimates
+    fn heuristic(&self, node: &Node) -> usize {
+        self.target.x.abs_diff(node.coord.x) + self.target.y.abs_diff(node.coord.y)
+    }
 }
 
 // fn shortest_path(target: (usize, usize), city: &City) -> usize {
@@ -366,12 +574,12 @@ where
 
 type Line<'a> = Vec<u8>;
 
-fn parse_input(input: &str) -> IResult<&str, Vec<Line>> {
+fn parse_input(input: &str) -> IResult<&str, Vec<Line<'_>>> {
     let (input, lines) = separated_list1(newline, parse_line)(input)?;
     Ok((input, lines))
 }
 
-fn parse_line(input: &str) -> IResult<&str, Line> {
+fn parse_line(input: &str) -> IResult<&str, Line<'_>> {
     let (input, line) = many1(parse_digit)(input)?;
     Ok((input, line))
 }
@@ -423,4 +631,85 @@ mod tests {
         let result = process_part2(input);
         assert_eq!(result, "94");
     }
+
+    #[test]
+    fn part2_minimum_steps_at_target() {
+        // it is cheaper to turn into the corner right before it, but the crucible can't stop there
+        let input = "111111111111
+999999999991
+999999999991
+999999999991
+999999999991";
+        let result = process_part2(input);
+        assert_eq!(result, "71");
+        assert_eq!(heat_loss_baseline(input, Rules::Ultra), Some(71));
+        assert!(render_svg(input, Rules::Ultra).contains("<title>Heat loss: 71</title>"));
+    }
+
+    #[test]
+    fn render() {
+        let input = "2413432311323
+3215453535623
+3255245654254
+3446585845452
+4546657867536
+1438598798454
+4457876987766
+3637877979653
+4654967986887
+4564679986453
+1224686865563
+2546548887735
+4322674655533";
+        let result = render_path(input, Rules::Normal);
+        // there are several optimal paths, so check the heat loss of the blocks with an arrow
+        let heat_loss = result
+            .chars()
+            .zip(input.chars())
+            .filter(|(rendered, _)| "^v<>".contains(*rendered))
+            .map(|(_, digit)| digit.to_digit(10).unwrap())
+            .sum::<u32>();
+        assert_eq!(heat_loss, 102);
+
+        let result = render_path(input, Rules::Ultra);
+        assert_eq!(
+            result,
+            "2>>>>>>>>1323
+32154535v5623
+32552456v4254
+34465858v5452
+45466578v>>>>
+143859879845v
+445787698776v
+363787797965v
+465496798688v
+456467998645v
+122468686556v
+254654888773v
+432267465553v"
+        );
+
+        let svg = render_svg(input, Rules::Ultra);
+        assert_eq!(svg.matches("<rect").count(), 13 * 13);
+        assert!(svg.contains("<title>Heat loss: 94</title>"));
+    }
+
+    #[test]
+    fn dense_matches_baseline() {
+        let input = "2413432311323
+3215453535623
+3255245654254
+3446585845452
+4546657867536
+1438598798454
+4457876987766
+3637877979653
+4654967986887
+4564679986453
+1224686865563
+2546548887735
+4322674655533";
+        assert_eq!(heat_loss_baseline(input, Rules::Normal), Some(102));
+        assert_eq!(heat_loss_baseline(input, Rules::Ultra), Some(94));
+    }
 }
@@ -0,0 +1,14 @@
+use answer::{render_svg, Plan};
+use std::fs;
+
+fn main() {
+    let file = fs::read_to_string("./input.txt").unwrap();
+    for (plan, name, max_size) in [
+        (Plan::Original, "part-1", None),
+        (Plan::Corrected, "part-2", Some(1000)),
+    ] {
+        let svg_name = format!("./{name}.svg");
+        fs::write(&svg_name, render_svg(&file, plan, max_size)).unwrap();
+        println!("Written {svg_name}");
+    }
+}
@@ -1,3 +1,5 @@
+pub mod polygon;
+
 use std::str::FromStr;
 
 use nom::{
@@ -8,11 +10,13 @@ use nom::{
     sequence::{preceded, terminated},
     IResult,
 };
+use polygon::{BoundingBox, Polygon, PolygonError};
 
 pub fn process_part1(input: &str) -> String {
     let (input, trenches) = parse_input(input).unwrap();
     assert!(input.is_empty());
-    let lagoon = calculate_area(trenches);
+    let lagoon =
+        calculate_area(trenches).expect("The dig plan is a loop that doesn't cross itself");
 
     lagoon.to_string()
 }
@@ -21,15 +25,75 @@ pub fn process_part2(input: &str) -> String {
     let (input, mut trenches) = parse_input(input).unwrap();
     assert!(input.is_empty());
     trenches.iter_mut().for_each(|t| *t = t.correct());
-    let lagoon = calculate_area(trenches);
+    let lagoon =
+        calculate_area(trenches).expect("The dig plan is a loop that doesn't cross itself");
 
     lagoon.to_string()
 }
 
-fn calculate_area(trenches: Vec<Trench>) -> u64 {
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Plan {
+    /// The directions and lengths as they are written down
+    Original,
+    /// Decoded from the hexadecimal codes
+    Corrected,
+}
+
+/// SVG of the lagoon with every trench in its colour. One meter is 10 pixels, unless that makes the
+/// image larger than `max_size` pixels, then it's scaled down to fit.
+pub fn render_svg(input: &str, plan: Plan, max_size: Option<u32>) -> String {
+    const BLOCK: f64 = 10.0;
+    let (input, trenches) = parse_input(input).unwrap();
+    assert!(input.is_empty());
+    let colours: Vec<&str> = trenches.iter().map(|t| t.hex).collect();
+    let trenches: Vec<Trench> = match plan {
+        Plan::Original => trenches,
+        Plan::Corrected => trenches.iter().map(|t| t.correct()).collect(),
+    };
+    let points = dig_path(&trenches);
+    let polygon =
+        Polygon::from_path(&points).expect("The dig plan is a loop that doesn't cross itself");
+
+    // every cube is centred on its coordinates
+    let BoundingBox { min, max } = polygon.bounding_box();
+    let (width, height) = ((max.0 - min.0 + 1) as f64, (max.1 - min.1 + 1) as f64);
+    let scale = max_size.map_or(BLOCK, |size| BLOCK.min(size as f64 / width.max(height)));
+    // keep the trenches visible when scaled down
+    let stroke_width = (1.0_f64).max(1.0 / scale);
+
+    let mut svg = format!(
+        "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{}\" height=\"{}\" viewBox=\"{} {} {width} {height}\">\n",
+        (width * scale).round(),
+        (height * scale).round(),
+        min.0 as f64 - 0.5,
+        min.1 as f64 - 0.5,
+    );
+    let corners = polygon
+        .corners()
+        .iter()
+        .map(|(x, y)| format!("{x},{y}"))
+        .collect::<Vec<_>>()
+        .join(" ");
+    svg.push_str(&format!(
+        "<polygon points=\"{corners}\" fill=\"dimgray\"/>\n"
+    ));
+    for (line, colour) in points.windows(2).zip(colours) {
+        let ((x1, y1), (x2, y2)) = (line[0], line[1]);
+        svg.push_str(&format!(
+            "<line x1=\"{x1}\" y1=\"{y1}\" x2=\"{x2}\" y2=\"{y2}\" stroke=\"#{colour}\" stroke-width=\"{stroke_width}\" stroke-linecap=\"square\"/>\n"
+        ));
+    }
+    svg.push_str(&format!(
+        "<title>Lagoon: {} cubic meters</title>\n</svg>\n",
+        polygon.lattice_points()
+    ));
+    svg
+}
+
+/// Corners of the trench, starting and (hopefully) ending at (0, 0)
+fn dig_path(trenches: &[Trench]) -> Vec<(i64, i64)> {
     let (mut x, mut y) = (0_i64, 0_i64);
     let mut points = vec![(x, y)];
-    let mut perimeter = 0;
     for trench in trenches {
         match trench.direction {
             Direction::Up => {
@@ -46,17 +110,13 @@ fn calculate_area(trenches: Vec<Trench>) -> u64 {
             }
         }
         points.push((x, y));
-        perimeter += trench.length;
     }
-    assert_eq!((x, y), (0, 0));
     points
-        .iter()
-        .zip(points.iter().skip(1))
-        .map(|(&(x1, y1), &(x2, y2))| (y1 + y2) * (x1 - x2))
-        .sum::<i64>()
-        .unsigned_abs()
-        / 2
-        + (perimeter / 2 + 1) as u64
+}
+
+/// Number of cubic meters of lava the lagoon holds, the trench itself included
+fn calculate_area(trenches: Vec<Trench>) -> Result<u64, PolygonError> {
+    Ok(Polygon::from_path(&dig_path(&trenches))?.lattice_points())
 }
 
 #[derive(Debug)]
@@ -140,12 +200,12 @@ fn is_hex_digit(c: char) -> bool {
     c.is_ascii_hexdigit()
 }
 
-fn parse_input(input: &str) -> IResult<&str, Vec<Trench>> {
+fn parse_input(input: &str) -> IResult<&str, Vec<Trench<'_>>> {
     let (input, lines) = separated_list1(newline, parse_trench)(input)?;
     Ok((input, lines))
 }
 
-fn parse_trench(input: &str) -> IResult<&str, Trench> {
+fn parse_trench(input: &str) -> IResult<&str, Trench<'_>> {
     let (input, direction) = terminated(parse_direction, space1)(input)?;
     let (input, length) = terminated(parse_usize, space1)(input)?;
     let (input, rgb) = nom::sequence::delimited(
@@ -153,7 +213,7 @@ fn parse_trench(input: &str) -> IResult<&str, Trench> {
         preceded(nomchar('#'), take_while_m_n(6, 6, is_hex_digit)),
         nomchar(')'),
     )(input)?;
-    let trench = Trench::new(direction, length, rgb.into());
+    let trench = Trench::new(direction, length, rgb);
     Ok((input, trench))
 }
 
@@ -209,4 +269,53 @@ U 2 (#7a21e3)";
         let result = process_part2(input);
         assert_eq!(result, "952408144115");
     }
+
+    #[test]
+    fn render() {
+        let input = "R 6 (#70c710)
+D 5 (#0dc571)
+L 2 (#5713f0)
+D 2 (#d2c081)
+R 2 (#59c680)
+D 2 (#411b91)
+L 5 (#8ceee2)
+U 2 (#caa173)
+L 1 (#1b58a2)
+U 2 (#caa171)
+R 2 (#7807d2)
+U 3 (#a77fa3)
+L 2 (#015232)
+U 2 (#7a21e3)";
+        let svg = render_svg(input, Plan::Original, None);
+        let lines: Vec<&str> = svg.lines().collect();
+        assert_eq!(
+            lines[0],
+            "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"70\" height=\"100\" viewBox=\"-0.5 -0.5 7 10\">"
+        );
+        assert!(lines[1].starts_with("<polygon points=\"0,0 6,0 6,5 4,5 "));
+        assert_eq!(
+            lines[2],
+            "<line x1=\"0\" y1=\"0\" x2=\"6\" y2=\"0\" stroke=\"#70c710\" stroke-width=\"1\" stroke-linecap=\"square\"/>"
+        );
+        assert_eq!(lines.iter().filter(|l| l.starts_with("<line")).count(), 14);
+        assert_eq!(lines[16], "<title>Lagoon: 62 cubic meters</title>");
+
+        let svg = render_svg(input, Plan::Corrected, Some(500));
+        assert!(svg.starts_with(
+            "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"500\" height=\"500\" viewBox=\"-0.5 -0.5 1186329 1186329\">"
+        ));
+        assert!(svg.contains("<title>Lagoon: 952408144115 cubic meters</title>"));
+    }
+
+    #[test]
+    fn unclosed_plan() {
+        let (_, trenches) = parse_input("R 6 (#70c710)\nD 5 (#0dc571)\nL 6 (#5713f0)").unwrap();
+        assert_eq!(
+            calculate_area(trenches),
+            Err(PolygonError::NotClosed {
+                start: (0, 0),
+                end: (0, 5)
+            })
+        );
+    }
 }
@@ -0,0 +1,255 @@
+//! Simple polygons with their corners on whole number coordinates
+use std::{error::Error, fmt::Display};
+
+pub type Point = (i64, i64);
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum PolygonError {
+    /// The path ends somewhere else than where it started
+    NotClosed { start: Point, end: Point },
+    /// A polygon needs at least 3 corners
+    TooFewCorners,
+    /// The edges with these indices touch or cross, edge i goes from corner i to corner i + 1
+    SelfIntersecting(usize, usize),
+}
+
+impl Display for PolygonError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            PolygonError::NotClosed { start, end } => {
+                write!(f, "The path starts at {:?} but ends at {:?}", start, end)
+            }
+            PolygonError::TooFewCorners => f.write_str("A polygon needs at least 3 corners"),
+            PolygonError::SelfIntersecting(a, b) => {
+                write!(f, "Edges {} and {} of the polygon intersect", a, b)
+            }
+        }
+    }
+}
+
+impl Error for PolygonError {}
+
+/// Direction in which the corners go round, with the y axis pointing up
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Winding {
+    Clockwise,
+    CounterClockwise,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BoundingBox {
+    pub min: Point,
+    pub max: Point,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Polygon {
+    corners: Vec<Point>,
+}
+
+impl Polygon {
+    /// Polygon along a path that ends where it started, the edges can't cross or touch each other
+    pub fn from_path(path: &[Point]) -> Result<Self, PolygonError> {
+        let (Some(&start), Some(&end)) = (path.first(), path.last()) else {
+            return Err(PolygonError::TooFewCorners);
+        };
+        if start != end {
+            return Err(PolygonError::NotClosed { start, end });
+        }
+        let mut corners = path[..path.len() - 1].to_vec();
+        // edges without a length don't count
+        corners.dedup();
+        if corners.len() > 1 && corners.first() == corners.last() {
+            corners.pop();
+        }
+        if corners.len() < 3 {
+            return Err(PolygonError::TooFewCorners);
+        }
+        let polygon = Self { corners };
+        if let Some((a, b)) = polygon.find_intersection() {
+            return Err(PolygonError::SelfIntersecting(a, b));
+        }
+        Ok(polygon)
+    }
+
+    pub fn corners(&self) -> &[Point] {
+        &self.corners
+    }
+
+    /// (from, to) for every edge
+    pub fn edges(&self) -> impl Iterator<Item = (Point, Point)> + '_ {
+        self.corners
+            .iter()
+            .zip(self.corners.iter().cycle().skip(1))
+            .map(|(&a, &b)| (a, b))
+    }
+
+    /// Shoelace formula, positive for counter clockwise polygons
+    fn twice_signed_area(&self) -> i64 {
+        self.edges()
+            .map(|((x1, y1), (x2, y2))| x1 * y2 - x2 * y1)
+            .sum()
+    }
+
+    /// Twice the area, which is always a whole number for corners on whole coordinates
+    pub fn twice_area(&self) -> u64 {
+        self.twice_signed_area().unsigned_abs()
+    }
+
+    pub fn area(&self) -> f64 {
+        self.twice_area() as f64 / 2.0
+    }
+
+    /// Number of whole coordinates on the edges
+    pub fn boundary_points(&self) -> u64 {
+        self.edges()
+            .map(|((x1, y1), (x2, y2))| gcd(x1.abs_diff(x2), y1.abs_diff(y2)))
+            .sum()
+    }
+
+    /// Number of whole coordinates strictly inside, from Pick's theorem: A = i + b/2 - 1
+    pub fn interior_points(&self) -> u64 {
+        (self.twice_area() + 2 - self.boundary_points()) / 2
+    }
+
+    /// Number of whole coordinates inside or on the edges
+    pub fn lattice_points(&self) -> u64 {
+        self.interior_points() + self.boundary_points()
+    }
+
+    pub fn winding(&self) -> Winding {
+        if self.twice_signed_area() > 0 {
+            Winding::CounterClockwise
+        } else {
+            Winding::Clockwise
+        }
+    }
+
+    pub fn bounding_box(&self) -> BoundingBox {
+        let (xs, ys): (Vec<i64>, Vec<i64>) = self.corners.iter().cloned().unzip();
+        BoundingBox {
+            min: (*xs.iter().min().unwrap(), *ys.iter().min().unwrap()),
+            max: (*xs.iter().max().unwrap(), *ys.iter().max().unwrap()),
+        }
+    }
+
+    /// The first pair of edges that touch in any other way than one following the other
+    fn find_intersection(&self) -> Option<(usize, usize)> {
+        let edges: Vec<_> = self.edges().collect();
+        let n = edges.len();
+        for i in 0..n {
+            for j in i + 1..n {
+                let (a, b) = edges[i];
+                let (c, d) = edges[j];
+                let intersects = if j == i + 1 || (i == 0 && j == n - 1) {
+                    // neighbours share a corner, they only overlap if the second one turns back
+                    let (shared, before, after) = if j == i + 1 { (b, a, d) } else { (a, b, c) };
+                    orientation(before, shared, after) == 0
+                        && dot(sub(shared, before), sub(after, shared)) < 0
+                } else {
+                    segments_intersect(a, b, c, d)
+                };
+                if intersects {
+                    return Some((i, j));
+                }
+            }
+        }
+        None
+    }
+}
+
+fn gcd(a: u64, b: u64) -> u64 {
+    if b == 0 {
+        a
+    } else {
+        gcd(b, a % b)
+    }
+}
+
+fn sub(a: Point, b: Point) -> Point {
+    (a.0 - b.0, a.1 - b.1)
+}
+
+fn dot(a: Point, b: Point) -> i64 {
+    a.0 * b.0 + a.1 * b.1
+}
+
+/// Positive if a, b, c turn counter clockwise, 0 if they are on one line
+fn orientation(a: Point, b: Point, c: Point) -> i64 {
+    let (ab, ac) = (sub(b, a), sub(c, a));
+    (ab.0 * ac.1 - ab.1 * ac.0).signum()
+}
+
+/// Whether c is on the segment from a to b, given that it's on the same line
+fn on_segment(a: Point, b: Point, c: Point) -> bool {
+    a.0.min(b.0) <= c.0 && c.0 <= a.0.max(b.0) && a.1.min(b.1) <= c.1 && c.1 <= a.1.max(b.1)
+}
+
+fn segments_intersect(a: Point, b: Point, c: Point, d: Point) -> bool {
+    let (o1, o2) = (orientation(a, b, c), orientation(a, b, d));
+    let (o3, o4) = (orientation(c, d, a), orientation(c, d, b));
+    (o1 != o2 && o3 != o4)
+        || (o1 == 0 && on_segment(a, b, c))
+        || (o2 == 0 && on_segment(a, b, d))
+        || (o3 == 0 && on_segment(c, d, a))
+        || (o4 == 0 && on_segment(c, d, b))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn square() {
+        let polygon = Polygon::from_path(&[(0, 0), (4, 0), (4, 3), (0, 3), (0, 0)]).unwrap();
+        assert_eq!(polygon.twice_area(), 24);
+        assert_eq!(polygon.area(), 12.0);
+        assert_eq!(polygon.boundary_points(), 14);
+        assert_eq!(polygon.interior_points(), 6);
+        assert_eq!(polygon.lattice_points(), 20);
+        assert_eq!(polygon.winding(), Winding::CounterClockwise);
+        assert_eq!(
+            polygon.bounding_box(),
+            BoundingBox {
+                min: (0, 0),
+                max: (4, 3)
+            }
+        );
+
+        let triangle = Polygon::from_path(&[(0, 0), (0, 4), (2, 0), (0, 0)]).unwrap();
+        assert_eq!(triangle.winding(), Winding::Clockwise);
+        assert_eq!(triangle.boundary_points(), 8);
+        assert_eq!(triangle.interior_points(), 1);
+    }
+
+    #[test]
+    fn errors() {
+        assert_eq!(
+            Polygon::from_path(&[(0, 0), (4, 0), (4, 3)]),
+            Err(PolygonError::NotClosed {
+                start: (0, 0),
+                end: (4, 3)
+            })
+        );
+        assert_eq!(
+            Polygon::from_path(&[(0, 0), (4, 0), (0, 0)]),
+            Err(PolygonError::TooFewCorners)
+        );
+        // a figure of eight
+        let eight = [(0, 0), (2, 0), (2, 4), (4, 4), (4, 2), (0, 2), (0, 0)];
+        assert_eq!(
+            Polygon::from_path(&eight),
+            Err(PolygonError::SelfIntersecting(1, 4))
+        );
+        // turning back over the edge that was just dug
+        let back = [(0, 0), (4, 0), (4, 3), (4, 1), (0, 1), (0, 0)];
+        assert_eq!(
+            Polygon::from_path(&back),
+            Err(PolygonError::SelfIntersecting(1, 2))
+        );
+        assert_eq!(
+            PolygonError::SelfIntersecting(1, 4).to_string(),
+            "Edges 1 and 4 of the polygon intersect"
+        );
+    }
+}
@@ -0,0 +1,10 @@
+use answer::simplify;
+use std::fs;
+
+fn main() {
+    let file = fs::read_to_string("./input.txt").unwrap();
+    match simplify(&file) {
+        Ok(simplified) => println!("{simplified}"),
+        Err(error) => eprintln!("{error}"),
+    }
+}
@@ -1,9 +1,11 @@
 use std::{
     cmp::Ordering,
     collections::{HashMap, VecDeque},
+    fmt::Display,
     ops::RangeInclusive,
 };
 
+use graph::{Graph, GraphError};
 use nom::{
     branch::alt,
     bytes::complete::tag,
@@ -40,8 +42,60 @@ pub fn process_part1(input: &str) -> String {
 pub fn process_part2(input: &str) -> String {
     let (input, (workflows, _)) = parse_input(input).unwrap();
     assert!(input.is_empty());
+    let combinations = accepted_combinations(&workflows);
+
+    combinations.to_string()
+}
+
+/// Returns the puzzle input with its workflows reduced to an equivalent, smaller set. The
+/// workflows come out in topological order, so every workflow is listed before the ones it sends
+/// parts to. Fails if parts can go around in a cycle of workflows reachable from `in`.
+pub fn simplify(input: &str) -> Result<String, GraphError<&str>> {
+    let (_, parts) = input
+        .split_once("\n\n")
+        .expect("Input should have workflows and parts");
+    let (rest, (workflows, _)) = parse_input(input).unwrap();
+    assert!(rest.is_empty());
+    // with a cycle the workflows could send parts around forever, unless no part gets there
+    workflow_graph(&workflows).topological_sort()?;
+    let before = accepted_combinations(&workflows);
+    let workflows = optimise(workflows);
+    let after = accepted_combinations(&workflows);
+    assert_eq!(before, after, "Optimising must not change what's accepted");
+
+    let workflows = workflow_graph(&workflows)
+        .topological_sort()
+        .expect("Optimising doesn't introduce cycles")
+        .into_iter()
+        .map(|id| workflows[id].to_string())
+        .collect::<Vec<_>>()
+        .join("\n");
+    Ok(format!("{workflows}\n\n{parts}"))
+}
+
+/// The parts accepted by the workflows in the input, as disjoint boxes of ratings
+pub fn accepted_region(input: &str) -> Region {
+    let (input, (workflows, _)) = parse_input(input).unwrap();
+    assert!(input.is_empty());
+    sort_possible_parts(&workflows).0
+}
+
+/// The parts rejected by the workflows in the input, as disjoint boxes of ratings
+pub fn rejected_region(input: &str) -> Region {
+    let (input, (workflows, _)) = parse_input(input).unwrap();
+    assert!(input.is_empty());
+    sort_possible_parts(&workflows).1
+}
+
+fn accepted_combinations(workflows: &HashMap<&str, Workflow>) -> usize {
+    sort_possible_parts(workflows).0.volume()
+}
+
+/// (accepted, rejected)
+fn sort_possible_parts(workflows: &HashMap<&str, Workflow>) -> (Region, Region) {
     // (workflow_id, PossiblePart)
     let mut accepted = Vec::new();
+    let mut rejected = Vec::new();
     let mut queue: VecDeque<(&str, PossiblePart)> = VecDeque::new();
     queue.push_back(("in", PossiblePart::default()));
     while let Some((workflow_id, mut possible_part)) = queue.pop_front() {
@@ -49,10 +103,12 @@ pub fn process_part2(input: &str) -> String {
         for rule in workflow.rules.iter() {
             let (next, more) = rule.apply_possible(possible_part);
             if let Some((target, more_possible)) = more {
-                match target {
-                    Target::Rejected => {} // we're done with this part
-                    Target::Accepted => accepted.push(more_possible),
-                    Target::Workflow(id) => queue.push_back((id, more_possible)),
+                if let Some(more_possible) = more_possible.remaining() {
+                    match target {
+                        Target::Rejected => rejected.push(more_possible),
+                        Target::Accepted => accepted.push(more_possible),
+                        Target::Workflow(id) => queue.push_back((id, more_possible)),
+                    }
                 }
             }
             if let Some(next) = next {
@@ -62,12 +118,175 @@ pub fn process_part2(input: &str) -> String {
             }
         }
     }
-    let combinations = accepted.iter().map(|p| p.combinations()).sum::<usize>();
+    (Region { boxes: accepted }, Region { boxes: rejected })
+}
 
-    combinations.to_string()
+/// Repeatedly prunes, merges and inlines workflows until nothing changes anymore
+fn optimise<'a>(mut workflows: HashMap<&'a str, Workflow<'a>>) -> HashMap<&'a str, Workflow<'a>> {
+    loop {
+        let mut changed = prune_dead_rules(&mut workflows);
+        changed |= merge_equivalent(&mut workflows);
+        changed |= inline_single_use(&mut workflows);
+        if !changed {
+            return workflows;
+        }
+    }
 }
 
-#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+/// Removes unreachable workflows and rules that no possible part can ever match
+fn prune_dead_rules<'a>(workflows: &mut HashMap<&'a str, Workflow<'a>>) -> bool {
+    // workflow_id -> for every rule, whether any possible part was sent to its target
+    let mut used: HashMap<&str, Vec<bool>> = HashMap::new();
+    let mut queue: VecDeque<(&str, PossiblePart)> = VecDeque::new();
+    queue.push_back(("in", PossiblePart::default()));
+    while let Some((workflow_id, mut possible_part)) = queue.pop_front() {
+        let workflow = workflows.get(workflow_id).expect("Workflow should exist");
+        let used = used
+            .entry(workflow_id)
+            .or_insert_with(|| vec![false; workflow.rules.len()]);
+        for (i, rule) in workflow.rules.iter().enumerate() {
+            let (next, more) = rule.apply_possible(possible_part);
+            if let Some((target, more_possible)) = more {
+                if let Some(more_possible) = more_possible.remaining() {
+                    used[i] = true;
+                    if let Target::Workflow(id) = target {
+                        queue.push_back((id, more_possible));
+                    }
+                }
+            }
+            if let Some(next) = next {
+                possible_part = next;
+            } else {
+                break;
+            }
+        }
+    }
+
+    let mut changed = false;
+    workflows.retain(|id, _| {
+        let reachable = used.contains_key(id);
+        changed |= !reachable;
+        reachable
+    });
+    for (id, workflow) in workflows.iter_mut() {
+        let mut used = used[id].iter();
+        let before = workflow.rules.len();
+        workflow.rules.retain(|_| *used.next().unwrap());
+        let fallback = last_target(workflow);
+        let last = workflow.rules.len() - 1;
+        if workflow.rules[last].compare != Ordering::Equal {
+            // nothing ever gets past the last rule that's still used, so it's a fallback
+            workflow.rules[last] = Rule::direct(fallback);
+            changed = true;
+        }
+        // a condition right before a fallback to the same target makes no difference
+        while workflow.rules.len() > 1
+            && workflow.rules[workflow.rules.len() - 2].target == fallback
+        {
+            workflow.rules.remove(workflow.rules.len() - 2);
+        }
+        changed |= workflow.rules.len() != before;
+    }
+    changed
+}
+
+/// Replaces workflows that always send parts to a single target, and workflows with identical
+/// rules, by one representative
+fn merge_equivalent<'a>(workflows: &mut HashMap<&'a str, Workflow<'a>>) -> bool {
+    let mut ids = workflows.keys().copied().collect::<Vec<_>>();
+    // keep "in" as representative and make the choice of the others deterministic
+    ids.sort_by_key(|&id| (id != "in", id));
+    let mut representatives: HashMap<&[Rule], &str> = HashMap::new();
+    let mut replacements = Vec::new();
+    for &id in ids.iter() {
+        let workflow = &workflows[id];
+        if id == "in" {
+            representatives.insert(&workflow.rules, id);
+        } else if workflow.rules.len() == 1 {
+            replacements.push((id, last_target(workflow)));
+        } else if let Some(&representative) = representatives.get(workflow.rules.as_slice()) {
+            replacements.push((id, Target::Workflow(representative)));
+        } else {
+            representatives.insert(&workflow.rules, id);
+        }
+    }
+    // only handle one replacement per workflow per pass, the targets may have been replaced too
+    let Some(&(id, target)) = replacements.first() else {
+        return false;
+    };
+    workflows.remove(id);
+    for workflow in workflows.values_mut() {
+        for rule in workflow.rules.iter_mut() {
+            if rule.target == Target::Workflow(id) {
+                rule.target = target;
+            }
+        }
+    }
+    true
+}
+
+/// Moves the rules of workflows that are only used as a single fallback into that fallback
+fn inline_single_use<'a>(workflows: &mut HashMap<&'a str, Workflow<'a>>) -> bool {
+    let mut uses: HashMap<&str, usize> = HashMap::new();
+    for rule in workflows.values().flat_map(|w| w.rules.iter()) {
+        if let Target::Workflow(id) = rule.target {
+            *uses.entry(id).or_default() += 1;
+        }
+    }
+    let mut ids = workflows.keys().copied().collect::<Vec<_>>();
+    ids.sort();
+    for id in ids {
+        let Target::Workflow(fallback) = last_target(&workflows[id]) else {
+            continue;
+        };
+        if fallback == "in" || fallback == id || uses.get(fallback) != Some(&1) {
+            continue;
+        }
+        let inlined = workflows.remove(fallback).expect("Workflow should exist");
+        let workflow = workflows.get_mut(id).expect("Workflow should exist");
+        workflow.rules.pop();
+        workflow.rules.extend(inlined.rules);
+        return true;
+    }
+    false
+}
+
+fn last_target<'a>(workflow: &Workflow<'a>) -> Target<'a> {
+    workflow
+        .rules
+        .last()
+        .expect("Workflow should always have a fallback")
+        .target
+}
+
+/// The workflows parts can reach from `in`, with an edge for every rule sending parts on
+fn workflow_graph<'a>(workflows: &HashMap<&'a str, Workflow<'a>>) -> Graph<&'a str> {
+    let mut ids = vec!["in"];
+    let mut queue = VecDeque::from(["in"]);
+    while let Some(id) = queue.pop_front() {
+        for rule in workflows[id].rules.iter() {
+            if let Target::Workflow(next) = rule.target {
+                if !ids.contains(&next) {
+                    ids.push(next);
+                    queue.push_back(next);
+                }
+            }
+        }
+    }
+    ids.sort_by_key(|&id| (id != "in", id));
+    let mut graph = Graph::new();
+    for id in ids {
+        graph.add_node(id);
+        for rule in workflows[id].rules.iter() {
+            if let Target::Workflow(next) = rule.target {
+                graph.add_edge(id, next, 1);
+            }
+        }
+    }
+    graph
+}
+
+#[derive(Debug, PartialEq, Eq, Hash, Clone, Copy)]
 enum Rating {
     X,
     M,
@@ -75,7 +294,7 @@ enum Rating {
     S,
 }
 
-#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+#[derive(Debug, PartialEq, Eq, Hash, Clone, Copy)]
 enum Target<'a> {
     Accepted,
     Rejected,
@@ -92,7 +311,17 @@ impl<'a> Target<'a> {
     }
 }
 
-#[derive(Debug, PartialEq, Eq)]
+impl Display for Target<'_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Accepted => write!(f, "A"),
+            Self::Rejected => write!(f, "R"),
+            Self::Workflow(id) => write!(f, "{id}"),
+        }
+    }
+}
+
+#[derive(Debug, PartialEq, Eq, Hash)]
 struct Rule<'a> {
     rating: Rating,
     compare: Ordering,
@@ -142,7 +371,7 @@ impl<'a> Rule<'a> {
 
     /// (Some(if any possible part makes it 'past' this rule), Some(if this rule sends a possible part to a target))
     fn apply_possible(
-        &'a self,
+        &self,
         possible_part: PossiblePart,
     ) -> (Option<PossiblePart>, Option<(Target<'a>, PossiblePart)>) {
         match self.compare {
@@ -159,6 +388,23 @@ impl<'a> Rule<'a> {
     }
 }
 
+impl Display for Rule<'_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let compare = match self.compare {
+            Ordering::Equal => return write!(f, "{}", self.target),
+            Ordering::Less => '<',
+            Ordering::Greater => '>',
+        };
+        let rating = match self.rating {
+            Rating::X => 'x',
+            Rating::M => 'm',
+            Rating::A => 'a',
+            Rating::S => 's',
+        };
+        write!(f, "{}{}{}:{}", rating, compare, self.value, self.target)
+    }
+}
+
 #[derive(Debug, PartialEq, Eq)]
 struct Workflow<'a> {
     id: &'a str,
@@ -176,8 +422,20 @@ impl<'a> Workflow<'a> {
     }
 }
 
+impl Display for Workflow<'_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let rules = self
+            .rules
+            .iter()
+            .map(|rule| rule.to_string())
+            .collect::<Vec<_>>()
+            .join(",");
+        write!(f, "{}{{{}}}", self.id, rules)
+    }
+}
+
 #[derive(Debug, Clone)]
-struct Part {
+pub struct Part {
     x: usize,
     m: usize,
     a: usize,
@@ -185,7 +443,7 @@ struct Part {
 }
 
 impl Part {
-    fn new(x: usize, m: usize, a: usize, s: usize) -> Self {
+    pub fn new(x: usize, m: usize, a: usize, s: usize) -> Self {
         Self { x, m, a, s }
     }
 
@@ -203,8 +461,9 @@ impl Part {
     }
 }
 
-#[derive(Debug)]
-struct PossiblePart {
+/// All parts with ratings within the (inclusive) ranges
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PossiblePart {
     x: RangeInclusive<usize>,
     m: RangeInclusive<usize>,
     a: RangeInclusive<usize>,
@@ -212,50 +471,96 @@ struct PossiblePart {
 }
 
 impl PossiblePart {
+    pub fn new(
+        x: RangeInclusive<usize>,
+        m: RangeInclusive<usize>,
+        a: RangeInclusive<usize>,
+        s: RangeInclusive<usize>,
+    ) -> Self {
+        Self { x, m, a, s }
+    }
+
+    pub fn x(&self) -> RangeInclusive<usize> {
+        self.x.clone()
+    }
+
+    pub fn m(&self) -> RangeInclusive<usize> {
+        self.m.clone()
+    }
+
+    pub fn a(&self) -> RangeInclusive<usize> {
+        self.a.clone()
+    }
+
+    pub fn s(&self) -> RangeInclusive<usize> {
+        self.s.clone()
+    }
+
+    pub fn contains(&self, part: &Part) -> bool {
+        self.x.contains(&part.x)
+            && self.m.contains(&part.m)
+            && self.a.contains(&part.a)
+            && self.s.contains(&part.s)
+    }
+
+    /// returns None if the overlap is empty
+    pub fn intersect(&self, other: &Self) -> Option<Self> {
+        let overlap = |a: &RangeInclusive<usize>, b: &RangeInclusive<usize>| {
+            (*a.start()).max(*b.start())..=(*a.end()).min(*b.end())
+        };
+        Self::new(
+            overlap(&self.x, &other.x),
+            overlap(&self.m, &other.m),
+            overlap(&self.a, &other.a),
+            overlap(&self.s, &other.s),
+        )
+        .remaining()
+    }
+
     #[allow(clippy::reversed_empty_ranges)]
-    /// Value is included in 'left' half of range
+    /// Value is included in 'left' half of range, neither half extends beyond the original range
     fn split(&self, rating: Rating, value: usize) -> (PossiblePart, PossiblePart) {
         let (left_x, left_m, left_a, left_s, right_x, right_m, right_a, right_s);
         match rating {
             Rating::X => {
-                left_x = (*self.x.start())..=value;
+                left_x = (*self.x.start())..=value.min(*self.x.end());
                 left_m = self.m.clone();
                 left_a = self.a.clone();
                 left_s = self.s.clone();
-                right_x = (value + 1)..=(*self.x.end());
+                right_x = (value + 1).max(*self.x.start())..=(*self.x.end());
                 right_m = self.m.clone();
                 right_a = self.a.clone();
                 right_s = self.s.clone();
             }
             Rating::M => {
                 left_x = self.x.clone();
-                left_m = (*self.m.start())..=value;
+                left_m = (*self.m.start())..=value.min(*self.m.end());
                 left_a = self.a.clone();
                 left_s = self.s.clone();
                 right_x = self.x.clone();
-                right_m = (value + 1)..=(*self.m.end());
+                right_m = (value + 1).max(*self.m.start())..=(*self.m.end());
                 right_a = self.a.clone();
                 right_s = self.s.clone();
             }
             Rating::A => {
                 left_x = self.x.clone();
                 left_m = self.m.clone();
-                left_a = (*self.a.start())..=value;
+                left_a = (*self.a.start())..=value.min(*self.a.end());
                 left_s = self.s.clone();
                 right_x = self.x.clone();
                 right_m = self.m.clone();
-                right_a = (value + 1)..=(*self.a.end());
+                right_a = (value + 1).max(*self.a.start())..=(*self.a.end());
                 right_s = self.s.clone();
             }
             Rating::S => {
                 left_x = self.x.clone();
                 left_m = self.m.clone();
                 left_a = self.a.clone();
-                left_s = (*self.s.start())..=value;
+                left_s = (*self.s.start())..=value.min(*self.s.end());
                 right_x = self.x.clone();
                 right_m = self.m.clone();
                 right_a = self.a.clone();
-                right_s = (value + 1)..=(*self.s.end());
+                right_s = (value + 1).max(*self.s.start())..=(*self.s.end());
             }
         }
         (
@@ -283,7 +588,7 @@ impl PossiblePart {
         }
     }
 
-    fn combinations(&self) -> usize {
+    pub fn combinations(&self) -> usize {
         self.x.clone().count()
             * self.m.clone().count()
             * self.a.clone().count()
@@ -302,7 +607,37 @@ impl Default for PossiblePart {
     }
 }
 
-fn parse_input(input: &str) -> IResult<&str, (HashMap<&str, Workflow>, Vec<Part>)> {
+/// A set of parts made up of disjoint boxes of ratings
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Region {
+    boxes: Vec<PossiblePart>,
+}
+
+impl Region {
+    pub fn boxes(&self) -> &[PossiblePart] {
+        &self.boxes
+    }
+
+    pub fn contains(&self, part: &Part) -> bool {
+        self.boxes.iter().any(|b| b.contains(part))
+    }
+
+    /// Number of distinct parts in the region
+    pub fn volume(&self) -> usize {
+        self.boxes.iter().map(|b| b.combinations()).sum()
+    }
+
+    pub fn intersect(&self, possible_part: &PossiblePart) -> Self {
+        let boxes = self
+            .boxes
+            .iter()
+            .filter_map(|b| b.intersect(possible_part))
+            .collect();
+        Self { boxes }
+    }
+}
+
+fn parse_input(input: &str) -> IResult<&str, (HashMap<&str, Workflow<'_>>, Vec<Part>)> {
     let (input, workflows) =
         terminated(separated_list1(newline, parse_workflow), tag("\n\n"))(input)?;
     let workflows = HashMap::from_iter(workflows.into_iter().map(|w| (w.id, w)));
@@ -310,7 +645,7 @@ fn parse_input(input: &str) -> IResult<&str, (HashMap<&str, Workflow>, Vec<Part>
     Ok((input, (workflows, parts)))
 }
 
-fn parse_workflow(input: &str) -> IResult<&str, Workflow> {
+fn parse_workflow(input: &str) -> IResult<&str, Workflow<'_>> {
     let (input, id) = alpha1(input)?;
     let (input, rules) = delimited(
         nomchar('{'),
@@ -321,12 +656,12 @@ fn parse_workflow(input: &str) -> IResult<&str, Workflow> {
     Ok((input, workflow))
 }
 
-fn parse_rule(input: &str) -> IResult<&str, Rule> {
+fn parse_rule(input: &str) -> IResult<&str, Rule<'_>> {
     let (input, rule) = alt((parse_conditional_rule, parse_direct_rule))(input)?;
     Ok((input, rule))
 }
 
-fn parse_conditional_rule(input: &str) -> IResult<&str, Rule> {
+fn parse_conditional_rule(input: &str) -> IResult<&str, Rule<'_>> {
     let (input, rating) = one_of("xmas")(input)?;
     let (input, compare) = one_of("<>")(input)?;
     let (input, value) = terminated(nomu64, nomchar(':'))(input)?;
@@ -350,7 +685,7 @@ fn parse_conditional_rule(input: &str) -> IResult<&str, Rule> {
     Ok((input, rule))
 }
 
-fn parse_direct_rule(input: &str) -> IResult<&str, Rule> {
+fn parse_direct_rule(input: &str) -> IResult<&str, Rule<'_>> {
     let (input, id) = alpha1(input)?;
     let target = Target::new(id);
     let rule = Rule::direct(target);
@@ -421,4 +756,107 @@ hdj{m>838:A,pv}
         let result = process_part2(input);
         assert_eq!(result, "167409079868000");
     }
+
+    #[test]
+    fn simplify_example() {
+        let input = "px{a<2006:qkq,m>2090:A,rfg}
+pv{a>1716:R,A}
+lnx{m>1548:A,A}
+rfg{s<537:gd,x>2440:R,A}
+qs{s>3448:A,lnx}
+qkq{x<1416:A,crn}
+crn{x>2662:A,R}
+in{s<1351:px,qqz}
+qqz{s>2770:qs,m<1801:hdj,R}
+gd{a>3333:R,R}
+hdj{m>838:A,pv}
+
+{x=787,m=2655,a=1222,s=2876}
+{x=1679,m=44,a=2067,s=496}
+{x=2036,m=264,a=79,s=2244}
+{x=2461,m=1339,a=466,s=291}
+{x=2127,m=1623,a=2188,s=1013}";
+        let result = simplify(input).unwrap();
+        assert!(result.starts_with(
+            "in{s<1351:px,s>2770:A,m<1801:hdj,R}
+px{a<2006:qkq,m>2090:A,s<537:R,x>2440:R,A}
+hdj{m>838:A,a>1716:R,A}
+qkq{x<1416:A,x>2662:A,R}
+
+"
+        ));
+        assert_eq!(process_part1(&result), "19114");
+        assert_eq!(process_part2(&result), "167409079868000");
+    }
+
+    #[test]
+    fn regions() {
+        let input = "px{a<2006:qkq,m>2090:A,rfg}
+pv{a>1716:R,A}
+lnx{m>1548:A,A}
+rfg{s<537:gd,x>2440:R,A}
+qs{s>3448:A,lnx}
+qkq{x<1416:A,crn}
+crn{x>2662:A,R}
+in{s<1351:px,qqz}
+qqz{s>2770:qs,m<1801:hdj,R}
+gd{a>3333:R,R}
+hdj{m>838:A,pv}
+
+{x=787,m=2655,a=1222,s=2876}
+{x=1679,m=44,a=2067,s=496}
+{x=2036,m=264,a=79,s=2244}
+{x=2461,m=1339,a=466,s=291}
+{x=2127,m=1623,a=2188,s=1013}";
+        let accepted = accepted_region(input);
+        let rejected = rejected_region(input);
+        assert_eq!(accepted.volume(), 167409079868000);
+        assert_eq!(accepted.volume() + rejected.volume(), 4000usize.pow(4));
+
+        assert!(accepted.contains(&Part::new(787, 2655, 1222, 2876)));
+        assert!(rejected.contains(&Part::new(1679, 44, 2067, 496)));
+        assert!(accepted.contains(&Part::new(2036, 264, 79, 2244)));
+        assert!(rejected.contains(&Part::new(2461, 1339, 466, 291)));
+        assert!(accepted.contains(&Part::new(2127, 1623, 2188, 1013)));
+
+        let low_x = PossiblePart::new(1..=2000, 1..=4000, 1..=4000, 1..=4000);
+        let high_x = PossiblePart::new(2001..=4000, 1..=4000, 1..=4000, 1..=4000);
+        let accepted_high_x = accepted.intersect(&high_x);
+        assert!(accepted_high_x
+            .boxes()
+            .iter()
+            .all(|b| *b.x().start() > 2000));
+        assert_eq!(
+            accepted.intersect(&low_x).volume() + accepted_high_x.volume(),
+            accepted.volume()
+        );
+        assert_eq!(
+            accepted_high_x.volume() + rejected.intersect(&high_x).volume(),
+            high_x.combinations()
+        );
+    }
+
+    #[test]
+    fn simplify_cyclical() {
+        let input = "in{x<2000:ab,A}
+ab{m>1000:cd,R}
+cd{a<10:ab,A}
+
+{x=787,m=2655,a=1222,s=2876}";
+        assert_eq!(
+            simplify(input),
+            Err(GraphError::GraphIsCyclical(vec!["cd", "ab"]))
+        );
+    }
+
+    #[test]
+    fn simplify_unreachable_cycle() {
+        let input = "in{x<2000:A,R}
+ab{m>1000:cd,R}
+cd{a<10:ab,A}
+
+{x=787,m=2655,a=1222,s=2876}";
+        let result = simplify(input).unwrap();
+        assert_eq!(result, "in{x<2000:A,R}\n\n{x=787,m=2655,a=1222,s=2876}");
+    }
 }
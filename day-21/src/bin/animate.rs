@@ -0,0 +1,21 @@
+use answer::{animation_frames, View};
+use std::{env, fs, thread, time::Duration};
+
+/// Plays the reachable plots in the terminal, `animate <steps> [<tiles>]`
+fn main() {
+    let file = fs::read_to_string("./input.txt").unwrap();
+    let mut args = env::args().skip(1);
+    let steps = args
+        .next()
+        .map_or(64, |s| s.parse().expect("Steps is a number"));
+    let view = args.next().map_or(View::Garden, |k| {
+        View::Tiled(k.parse().expect("Tiles is a number"))
+    });
+
+    for (step, frame) in animation_frames(&file, steps, view).iter().enumerate() {
+        // clear the screen and draw the reachable plots in green
+        print!("\x1b[2J\x1b[H{}", frame.replace('0', "\x1b[32m0\x1b[0m"));
+        println!("Step {step}: {} plots", frame.matches('0').count());
+        thread::sleep(Duration::from_millis(50));
+    }
+}
@@ -0,0 +1,37 @@
+use answer::{process_part2, reachable_plots_with, Strategy};
+use std::{fs, time::Instant};
+
+fn main() {
+    let file = fs::read_to_string("./input.txt").unwrap();
+    use Strategy::*;
+    for (steps, strategies) in [
+        (
+            10,
+            vec![Naive, Alternating, Expand, TiledDistances, Quadratic],
+        ),
+        (64, vec![Alternating, Expand, TiledDistances, Quadratic]),
+        (
+            65 + 2 * 131,
+            vec![Alternating, Expand, TiledDistances, Quadratic, Diamond],
+        ),
+        (26501365, vec![Quadratic, Diamond]),
+    ] {
+        let mut results = vec![];
+        for strategy in strategies {
+            let start = Instant::now();
+            let plots = reachable_plots_with(&file, steps, strategy).unwrap();
+            println!(
+                "{steps} steps, {strategy:?}: {plots} - {:?}",
+                start.elapsed()
+            );
+            results.push(plots);
+        }
+        assert!(
+            results.windows(2).all(|w| w[0] == w[1]),
+            "All strategies should agree"
+        );
+        if steps == 26501365 {
+            assert_eq!(results[0].to_string(), process_part2(&file));
+        }
+    }
+}
@@ -1,29 +1,161 @@
-use std::{collections::VecDeque, fmt::Debug};
+use std::collections::{HashSet, VecDeque};
+use std::error::Error;
+use std::fmt::{Debug, Display};
+
+use search::Movement;
 
 pub fn process_part1(input: &str) -> String {
     let target_steps = if input.len() == 131 { 6 } else { 64 };
-    let (garden, (start_x, start_y)) = parse_input(input);
+    let (garden, start) = parse_input(input);
     // print_garden(&garden);
-    let width = garden[0].len();
-    let height = garden.len();
-    let mut steps_to_reach = vec![vec![0_usize; width]; height];
-    let mut queue = VecDeque::new();
-    neighbours(start_x, start_y, &garden, &steps_to_reach)
-        .for_each(|(x, y)| queue.push_back(Node::new(x, y, 1)));
-    while let Some(Node { x, y, steps }) = queue.pop_front() {
-        if steps_to_reach[y][x] != 0 {
-            continue;
-        }
-        for (new_x, new_y) in neighbours(x, y, &garden, &steps_to_reach) {
-            queue.push_back(Node::new(new_x, new_y, steps + 1));
-        }
-        steps_to_reach[y][x] = steps;
-    }
+    let steps_to_reach = steps_to_reach(&garden, start);
     let plots_after_target_steps = count_plots(&steps_to_reach, target_steps);
     plots_after_target_steps.to_string()
 }
 
 pub fn process_part2(input: &str) -> String {
+    reachable_plots(input, 26501365)
+        .expect("The garden repeats in a way that lets the plots grow quadratically")
+        .to_string()
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Strategy {
+    /// Tries every possible walk, only feasible for a handful of steps
+    Naive,
+    /// Keeps track of the plots that can be reached after every step
+    Alternating,
+    /// Repeats the garden often enough to fit all the steps and walks that like Alternating
+    Expand,
+    /// The least number of steps to every plot of the garden repeated out to `steps` in every
+    /// direction, counting the ones with the right parity. A BFS over all of them, like
+    /// [reachable_plots_brute_force]
+    TiledDistances,
+    /// Follows the quadratic growth, see [reachable_plots]
+    Quadratic,
+    /// The least number of steps to every plot of a single garden, counted by parity with and
+    /// without the corners and scaled up to the number of gardens that are covered. Only works
+    /// for square gardens with clear paths from the start in the middle to the edges, for steps
+    /// that end at the edge of a garden
+    Diamond,
+}
+
+/// Number of plots that can be reached in exactly `steps` steps in the infinitely repeating
+/// garden, worked out with the given strategy
+pub fn reachable_plots_with(
+    input: &str,
+    steps: usize,
+    strategy: Strategy,
+) -> Result<usize, GardenError> {
+    if strategy == Strategy::Quadratic {
+        return reachable_plots(input, steps);
+    }
+    let (garden, start) = parse_input(input);
+    if garden.iter().any(|row| row.len() != garden[0].len()) {
+        return Err(GardenError::NotRectangular);
+    }
+    let plots = match strategy {
+        Strategy::Naive => naive(&garden, start, steps),
+        Strategy::Alternating => alternating(|point| is_plot_at(&garden, start, point), steps),
+        Strategy::Expand => {
+            let (garden, start) = expand(&garden, start, steps);
+            let (width, height) = (garden[0].len() as isize, garden.len() as isize);
+            let start = (start.0 as isize, start.1 as isize);
+            alternating(
+                |(x, y)| {
+                    let (x, y) = (x + start.0, y + start.1);
+                    (0..width).contains(&x)
+                        && (0..height).contains(&y)
+                        && garden[y as usize][x as usize].is_plot()
+                },
+                steps,
+            )
+        }
+        Strategy::TiledDistances => {
+            count_plots(&tiled_steps_to_reach(&garden, start, steps), steps)
+        }
+        Strategy::Quadratic => unreachable!("Handled above"),
+        Strategy::Diamond => diamond(&garden, start, steps)?,
+    };
+    Ok(plots)
+}
+
+/// Whether there is a plot at the point of the infinitely repeating garden, relative to the start
+fn is_plot_at(garden: &[Vec<Garden>], start: (usize, usize), (x, y): (isize, isize)) -> bool {
+    let (width, height) = (garden[0].len() as isize, garden.len() as isize);
+    let x = (x + start.0 as isize).rem_euclid(width) as usize;
+    let y = (y + start.1 as isize).rem_euclid(height) as usize;
+    garden[y][x].is_plot()
+}
+
+fn neighbours_of((x, y): (isize, isize)) -> [(isize, isize); 4] {
+    [(x - 1, y), (x + 1, y), (x, y - 1), (x, y + 1)]
+}
+
+fn naive(garden: &[Vec<Garden>], start: (usize, usize), steps: usize) -> usize {
+    fn walk(
+        garden: &[Vec<Garden>],
+        start: (usize, usize),
+        point: (isize, isize),
+        steps: usize,
+        reached: &mut HashSet<(isize, isize)>,
+    ) {
+        if steps == 0 {
+            reached.insert(point);
+            return;
+        }
+        neighbours_of(point)
+            .into_iter()
+            .filter(|&n| is_plot_at(garden, start, n))
+            .for_each(|n| walk(garden, start, n, steps - 1, reached));
+    }
+
+    let mut reached = HashSet::new();
+    walk(garden, start, (0, 0), steps, &mut reached);
+    reached.len()
+}
+
+/// Points are relative to the start
+fn alternating<F>(is_plot: F, steps: usize) -> usize
+where
+    F: Fn((isize, isize)) -> bool,
+{
+    let mut current = HashSet::from([(0, 0)]);
+    let mut next = HashSet::new();
+    for _ in 0..steps {
+        current.iter().for_each(|&point| {
+            next.extend(neighbours_of(point).into_iter().filter(|&n| is_plot(n)));
+        });
+        std::mem::swap(&mut current, &mut next);
+        next.clear();
+    }
+    current.len()
+}
+
+/// The garden repeated in every direction until all points `steps` away from the start fit
+fn expand(
+    garden: &[Vec<Garden>],
+    start: (usize, usize),
+    steps: usize,
+) -> (Vec<Vec<Garden>>, (usize, usize)) {
+    let (width, height) = (garden[0].len(), garden.len());
+    let (tiles_x, tiles_y) = (steps / width + 1, steps / height + 1);
+    let expanded = (0..(2 * tiles_y + 1) * height)
+        .map(|y| {
+            (0..(2 * tiles_x + 1) * width)
+                .map(|x| garden[y % height][x % width])
+                .collect()
+        })
+        .collect();
+    let start = (start.0 + tiles_x * width, start.1 + tiles_y * height);
+    (expanded, start)
+}
+
+fn diamond(
+    garden: &[Vec<Garden>],
+    start: (usize, usize),
+    steps: usize,
+) -> Result<usize, GardenError> {
     // The input has a diamond shape to it and the amount of steps is of the form ((65*2+1)*n)+65.
     // This insight derives from that we can reach the edge from the start point in 65 steps (thereby
     // covering the entire diamond) and we can reach the entire plot in 131 steps.
@@ -35,76 +167,221 @@ pub fn process_part2(input: &str) -> String {
     // where we're only covering the corners is n+1 and the number of even grids where we're only
     // covering the corners is n. So the total formula is n^2*evens + (n+1)^2*odds + n*even_corners
     // + (n+1)*odd_corners.
-    let (garden, (start_x, start_y)) = parse_input(input);
-    let width = garden[0].len();
-    let height = garden.len();
-    let mut steps_to_reach = vec![vec![0_usize; width]; height];
-    let mut queue = VecDeque::new();
-    neighbours(start_x, start_y, &garden, &steps_to_reach)
-        .for_each(|(x, y)| queue.push_back(Node::new(x, y, 1)));
-    while let Some(Node { x, y, steps }) = queue.pop_front() {
-        if steps_to_reach[y][x] != 0 {
-            continue;
-        }
-        for (new_x, new_y) in neighbours(x, y, &garden, &steps_to_reach) {
-            queue.push_back(Node::new(new_x, new_y, steps + 1));
-        }
-        steps_to_reach[y][x] = steps;
+    // Because the garden has an odd size, the (n+1)^2 grids reach the squares with the same parity
+    // as 65 for an odd n as well.
+    let size = garden.len();
+    let half = size / 2;
+    let clear_paths = (0..size).all(|i| garden[half][i].is_plot() && garden[i][half].is_plot());
+    if garden[0].len() != size
+        || size.is_multiple_of(2)
+        || start != (half, half)
+        || !clear_paths
+        || steps % size != half
+    {
+        return Err(GardenError::NotADiamond);
     }
 
-    // see logic above for the origins of the magic numbers
-    let even = count_plots(&steps_to_reach, 132);
-    let odd = count_plots(&steps_to_reach, 131);
-    let even_corners = steps_to_reach
-        .iter()
-        .flat_map(|row| row.iter())
-        .filter(|&&steps| steps > 65 && steps % 2 == 0)
-        .count();
-    let odd_corners = steps_to_reach
-        .iter()
-        .flat_map(|row| row.iter())
-        .filter(|&&steps| steps > 65 && steps % 2 == 1)
-        .count();
-    let target_steps = 26501365_usize;
-    let n = (target_steps - 65) / 131;
-    let reachable =
-        n.pow(2) * even + (n + 1).pow(2) * odd + n * even_corners - (n + 1) * odd_corners;
+    let steps_to_reach = steps_to_reach(garden, start);
+    let reachable = |parity: usize, beyond: Option<usize>| {
+        steps_to_reach
+            .iter()
+            .flat_map(|row| row.iter().flatten())
+            .filter(|&&steps| beyond.is_none_or(|b| steps > b) && steps % 2 == parity)
+            .count()
+    };
+    // same parity as the steps to the edge of the garden, the odds for the real input
+    let parity = half % 2;
+    let (same, other) = (reachable(parity, None), reachable(1 - parity, None));
+    let (same_corners, other_corners) = (
+        reachable(parity, Some(half)),
+        reachable(1 - parity, Some(half)),
+    );
+    let n = steps / size;
+    Ok((n + 1).pow(2) * same + n.pow(2) * other + n * other_corners - (n + 1) * same_corners)
+}
 
-    reachable.to_string()
+#[derive(Debug, PartialEq, Eq)]
+pub enum GardenError {
+    /// Rows of different lengths can't be tiled
+    NotRectangular,
+    /// The number of reachable plots doesn't grow quadratically with the number of tiles within
+    /// this many steps
+    NoQuadraticGrowth { steps_checked: usize },
+    /// The garden doesn't have the shape the diamond formula needs, or the steps don't end at the
+    /// edge of a garden
+    NotADiamond,
 }
 
-fn neighbours(
-    x: usize,
-    y: usize,
-    garden: &[Vec<Garden>],
-    steps_to_reach: &[Vec<usize>],
-) -> std::vec::IntoIter<(usize, usize)> {
-    let mut nbours: Vec<(usize, usize)> = Vec::new();
-    if x > 0 && steps_to_reach[y][x - 1] == 0 && garden[y][x - 1].is_plot() {
-        nbours.push((x - 1, y));
+impl Display for GardenError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            GardenError::NotRectangular => f.write_str("The garden is not rectangular"),
+            GardenError::NoQuadraticGrowth { steps_checked } => write!(
+                f,
+                "The reachable plots don't grow quadratically within {} steps",
+                steps_checked
+            ),
+            GardenError::NotADiamond => {
+                f.write_str("The diamond formula doesn't work for this garden")
+            }
+        }
     }
-    if x < (garden[0].len() - 1) && steps_to_reach[y][x + 1] == 0 && garden[y][x + 1].is_plot() {
-        nbours.push((x + 1, y));
+}
+
+impl Error for GardenError {}
+
+/// Number of plots that can be reached in exactly `steps` steps in the infinitely repeating garden.
+///
+/// After a while, every time the elf can walk another garden's width further, the number of
+/// reachable plots grows like a quadratic. The plots are counted with a BFS for a few gardens'
+/// worth of steps, until the second differences have settled, and then the quadratic is followed
+/// to the number of steps that was asked for. Gives up once the BFS would need more than
+/// `MAX_POINTS` points of the repeating garden.
+pub fn reachable_plots(input: &str, steps: usize) -> Result<usize, GardenError> {
+    const SETTLED: usize = 3;
+    /// The steps to every point take 16 bytes, so this is about 256 MB
+    const MAX_POINTS: usize = 1 << 24;
+    let (garden, start) = parse_input(input);
+    if garden.iter().any(|row| row.len() != garden[0].len()) {
+        return Err(GardenError::NotRectangular);
     }
-    if y > 0 && steps_to_reach[y - 1][x] == 0 && garden[y - 1][x].is_plot() {
-        nbours.push((x, y - 1));
+    let period = lcm(garden.len(), garden[0].len());
+    let remainder = steps % period;
+    let target = steps / period;
+
+    let mut periods = SETTLED + 1;
+    let mut steps_checked = 0;
+    loop {
+        let radius = remainder + periods * period;
+        let size = 2 * radius.min(steps) + 1;
+        if size * size > MAX_POINTS {
+            return Err(GardenError::NoQuadraticGrowth { steps_checked });
+        }
+        if radius >= steps {
+            let steps_to_reach = tiled_steps_to_reach(&garden, start, steps);
+            return Ok(count_plots(&steps_to_reach, steps));
+        }
+        let steps_to_reach = tiled_steps_to_reach(&garden, start, radius);
+        let plots: Vec<usize> = (0..=periods)
+            .map(|k| count_plots(&steps_to_reach, remainder + k * period))
+            .collect();
+        let second_differences: Vec<isize> = plots
+            .windows(3)
+            .map(|w| w[2] as isize - 2 * w[1] as isize + w[0] as isize)
+            .collect();
+        let last = second_differences[second_differences.len() - SETTLED..].to_vec();
+        if last.iter().all(|&d| d == last[0]) {
+            let m = (target - periods) as isize;
+            let first_difference = (plots[periods] - plots[periods - 1]) as isize;
+            let plots_at_target =
+                plots[periods] as isize + m * first_difference + m * (m + 1) / 2 * last[0];
+            return Ok(plots_at_target as usize);
+        }
+        steps_checked = radius;
+        periods *= 2;
     }
-    if y < (garden.len() - 1) && steps_to_reach[y + 1][x] == 0 && garden[y + 1][x].is_plot() {
-        nbours.push((x, y + 1));
+}
+
+/// Number of plots that can be reached in exactly `steps` steps in the infinitely repeating
+/// garden, by walking there
+pub fn reachable_plots_brute_force(input: &str, steps: usize) -> usize {
+    let (garden, start) = parse_input(input);
+    count_plots(&tiled_steps_to_reach(&garden, start, steps), steps)
+}
+
+fn lcm(a: usize, b: usize) -> usize {
+    let gcd = |mut a: usize, mut b: usize| {
+        while b != 0 {
+            (a, b) = (b, a % b);
+        }
+        a
+    };
+    a / gcd(a, b) * b
+}
+
+/// Walking over the plots of the garden, one step at a time
+struct Walk<'a> {
+    garden: &'a [Vec<Garden>],
+}
+
+impl Movement for Walk<'_> {
+    type State = (usize, usize);
+
+    fn neighbours(&self, &(x, y): &(usize, usize)) -> Vec<((usize, usize), usize)> {
+        let garden = self.garden;
+        let mut nbours = Vec::new();
+        if x > 0 && garden[y][x - 1].is_plot() {
+            nbours.push(((x - 1, y), 1));
+        }
+        if x < (garden[0].len() - 1) && garden[y][x + 1].is_plot() {
+            nbours.push(((x + 1, y), 1));
+        }
+        if y > 0 && garden[y - 1][x].is_plot() {
+            nbours.push(((x, y - 1), 1));
+        }
+        if y < (garden.len() - 1) && garden[y + 1][x].is_plot() {
+            nbours.push(((x, y + 1), 1));
+        }
+        nbours
     }
+}
+
+/// The least number of steps needed to reach every plot, None if it can't be reached
+fn steps_to_reach(garden: &[Vec<Garden>], start: (usize, usize)) -> Vec<Vec<Option<usize>>> {
+    let mut steps_to_reach = vec![vec![None; garden[0].len()]; garden.len()];
+    search::distances(&Walk { garden }, [start])
+        .into_iter()
+        .for_each(|((x, y), steps)| steps_to_reach[y][x] = Some(steps));
+    steps_to_reach
+}
+
+/// The least number of steps needed to reach every plot of the infinitely repeating garden, up to
+/// `radius` steps away. The start is at (radius, radius).
+fn tiled_steps_to_reach(
+    garden: &[Vec<Garden>],
+    start: (usize, usize),
+    radius: usize,
+) -> Vec<Vec<Option<usize>>> {
+    let (width, height) = (garden[0].len(), garden.len());
+    let size = 2 * radius + 1;
+    // offsets that put the start of the garden at (radius, radius)
+    let (dx, dy) = (
+        start.0 + width - radius % width,
+        start.1 + height - radius % height,
+    );
+    let is_plot = |x: usize, y: usize| garden[(y + dy) % height][(x + dx) % width].is_plot();
 
-    nbours.into_iter()
+    let mut steps_to_reach = vec![vec![None; size]; size];
+    steps_to_reach[radius][radius] = Some(0);
+    let mut queue = VecDeque::from([(radius, radius, 0)]);
+    while let Some((x, y, steps)) = queue.pop_front() {
+        if steps == radius {
+            continue;
+        }
+        for (x, y) in [
+            (x.wrapping_sub(1), y),
+            (x + 1, y),
+            (x, y.wrapping_sub(1)),
+            (x, y + 1),
+        ] {
+            if x < size && y < size && steps_to_reach[y][x].is_none() && is_plot(x, y) {
+                steps_to_reach[y][x] = Some(steps + 1);
+                queue.push_back((x, y, steps + 1));
+            }
+        }
+    }
+    steps_to_reach
 }
 
-fn count_plots(steps_to_reach: &[Vec<usize>], target_steps: usize) -> usize {
+fn count_plots(steps_to_reach: &[Vec<Option<usize>>], target_steps: usize) -> usize {
     steps_to_reach
         .iter()
-        .flat_map(|row| row.iter())
-        .filter(|&&steps| steps > 0 && steps <= target_steps && ((target_steps % 2) == steps % 2))
+        .flat_map(|row| row.iter().flatten())
+        .filter(|&&steps| steps <= target_steps && ((target_steps % 2) == steps % 2))
         .count()
 }
 
-#[derive(PartialEq, Eq)]
+#[derive(Clone, Copy, PartialEq, Eq)]
 enum Garden {
     Plot,
     Rock,
@@ -126,18 +403,6 @@ impl Debug for Garden {
     }
 }
 
-struct Node {
-    x: usize,
-    y: usize,
-    steps: usize,
-}
-
-impl Node {
-    fn new(x: usize, y: usize, steps: usize) -> Self {
-        Self { x, y, steps }
-    }
-}
-
 #[allow(dead_code)]
 fn print_garden(garden: &[Vec<Garden>]) {
     garden.iter().for_each(|row| {
@@ -147,7 +412,20 @@ fn print_garden(garden: &[Vec<Garden>]) {
 }
 
 #[allow(dead_code)]
-fn print_reachable_garden(garden: &[Vec<Garden>], steps_to_reach: &[Vec<usize>], steps: usize) {
+fn print_reachable_garden(
+    garden: &[Vec<Garden>],
+    steps_to_reach: &[Vec<Option<usize>>],
+    steps: usize,
+) {
+    print!("{}", render_reachable_garden(garden, steps_to_reach, steps));
+}
+
+fn render_reachable_garden(
+    garden: &[Vec<Garden>],
+    steps_to_reach: &[Vec<Option<usize>>],
+    steps: usize,
+) -> String {
+    let mut output = String::new();
     garden
         .iter()
         .zip(steps_to_reach.iter())
@@ -156,17 +434,63 @@ fn print_reachable_garden(garden: &[Vec<Garden>], steps_to_reach: &[Vec<usize>],
                 .iter()
                 .zip(row_steps)
                 .for_each(|(g, &s)| match g {
-                    Garden::Rock => print!("#"),
-                    Garden::Plot => {
-                        if s != 0 && s <= steps && (s % 2 == steps % 2) {
-                            print!("0")
-                        } else {
-                            print!(".")
-                        }
-                    }
+                    Garden::Rock => output.push('#'),
+                    Garden::Plot => match s {
+                        Some(s) if s <= steps && (s % 2 == steps % 2) => output.push('0'),
+                        _ => output.push('.'),
+                    },
                 });
-            println!();
-        })
+            output.push('\n');
+        });
+    output
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum View {
+    /// Only the garden itself, the elf can't leave it
+    Garden,
+    /// k by k copies of the infinitely repeating garden, with the start in the middle one
+    Tiled(usize),
+}
+
+/// A frame for every number of steps from 0 up to and including `steps`, with a `0` for every plot
+/// that can be reached in exactly that many steps
+pub fn animation_frames(input: &str, steps: usize, view: View) -> Vec<String> {
+    let (garden, start) = parse_input(input);
+    let (garden, steps_to_reach) = match view {
+        View::Garden => {
+            let steps_to_reach = steps_to_reach(&garden, start);
+            (garden, steps_to_reach)
+        }
+        View::Tiled(k) => {
+            let (width, height) = (garden[0].len(), garden.len());
+            // the start is at (steps, steps) in the tiled distances
+            let tiled = tiled_steps_to_reach(&garden, start, steps);
+            let (left, top) = (start.0 + k / 2 * width, start.1 + k / 2 * height);
+            let window_garden = (0..k * height)
+                .map(|y| {
+                    (0..k * width)
+                        .map(|x| garden[y % height][x % width])
+                        .collect()
+                })
+                .collect();
+            let window_steps = (0..k * height)
+                .map(|y| {
+                    (0..k * width)
+                        .map(|x| {
+                            let tiled_x = (steps + x).checked_sub(left)?;
+                            let tiled_y = (steps + y).checked_sub(top)?;
+                            *tiled.get(tiled_y)?.get(tiled_x)?
+                        })
+                        .collect()
+                })
+                .collect();
+            (window_garden, window_steps)
+        }
+    };
+    (0..=steps)
+        .map(|s| render_reachable_garden(&garden, &steps_to_reach, s))
+        .collect()
 }
 
 fn parse_input(input: &str) -> (Vec<Vec<Garden>>, (usize, usize)) {
@@ -222,4 +546,103 @@ mod tests {
         let result = process_part1(input);
         assert_eq!(result, "16");
     }
+
+    const EXAMPLE: &str = "...........
+.....###.#.
+.###.##..#.
+..#.#...#..
+....#.#....
+.##..S####.
+.##..#...#.
+.......##..
+.##.#.####.
+.##..##.##.
+...........";
+
+    #[test]
+    fn infinite_garden() {
+        for (steps, plots) in [(6, 16), (10, 50), (50, 1594), (100, 6536), (500, 167004)] {
+            assert_eq!(reachable_plots_brute_force(EXAMPLE, steps), plots);
+            assert_eq!(reachable_plots(EXAMPLE, steps), Ok(plots));
+        }
+        assert_eq!(reachable_plots(EXAMPLE, 1000), Ok(668697));
+        assert_eq!(reachable_plots(EXAMPLE, 5000), Ok(16733044));
+        for steps in 0..120 {
+            assert_eq!(
+                reachable_plots(EXAMPLE, steps),
+                Ok(reachable_plots_brute_force(EXAMPLE, steps)),
+                "{steps} steps"
+            );
+        }
+    }
+
+    #[test]
+    fn strategies() {
+        use Strategy::*;
+        for steps in 0..40 {
+            let expected = reachable_plots_brute_force(EXAMPLE, steps);
+            let strategies: &[Strategy] = if steps <= 8 {
+                &[Naive, Alternating, Expand, TiledDistances, Quadratic]
+            } else {
+                &[Alternating, Expand, TiledDistances, Quadratic]
+            };
+            for &strategy in strategies {
+                assert_eq!(
+                    reachable_plots_with(EXAMPLE, steps, strategy),
+                    Ok(expected),
+                    "{strategy:?} for {steps} steps"
+                );
+            }
+        }
+        // the example doesn't have clear paths from the start
+        assert_eq!(
+            reachable_plots_with(EXAMPLE, 16, Diamond),
+            Err(GardenError::NotADiamond)
+        );
+        let open = ".....\n.#...\n..S..\n...#.\n.....";
+        for n in 0..6 {
+            let steps = 5 * n + 2;
+            assert_eq!(
+                reachable_plots_with(open, steps, Diamond),
+                Ok(reachable_plots_brute_force(open, steps)),
+                "{steps} steps"
+            );
+        }
+    }
+
+    #[test]
+    fn animation() {
+        let frames = animation_frames(EXAMPLE, 6, View::Garden);
+        assert_eq!(frames.len(), 7);
+        assert_eq!(frames[0].matches('0').count(), 1);
+        assert_eq!(frames[6].matches('0').count(), 16);
+        assert_eq!(frames[2].lines().nth(5), Some(".##0.0####."));
+
+        let frames = animation_frames(EXAMPLE, 10, View::Tiled(3));
+        assert_eq!(frames[10].lines().count(), 33);
+        assert_eq!(frames[10].lines().next().map(str::len), Some(33));
+        assert_eq!(frames[10].matches('0').count(), 50);
+        assert_eq!(
+            frames[0].lines().nth(16),
+            Some(".##...####..##..0####..##...####.")
+        );
+    }
+
+    #[test]
+    fn too_wide_to_check() {
+        // a single garden already needs too many points to see it grow
+        let wide = format!("S{}", ".".repeat(2999));
+        assert_eq!(
+            reachable_plots(&wide, 26501365),
+            Err(GardenError::NoQuadraticGrowth { steps_checked: 0 })
+        );
+    }
+
+    #[test]
+    fn not_rectangular() {
+        assert_eq!(
+            reachable_plots("...\n.S\n...", 100),
+            Err(GardenError::NotRectangular)
+        );
+    }
 }
@@ -0,0 +1,15 @@
+use answer::{Colouring, Stack};
+use std::fs;
+
+fn main() {
+    let file = fs::read_to_string("./input.txt").unwrap();
+    let stack = Stack::parse(&file);
+    for (colouring, name) in [
+        (Colouring::Distinct, "tower"),
+        (Colouring::Falling, "falling"),
+    ] {
+        let obj_name = format!("./{name}.obj");
+        fs::write(&obj_name, stack.to_obj(colouring)).unwrap();
+        println!("Written {obj_name}");
+    }
+}
@@ -1,8 +1,10 @@
 use std::{
-    collections::{HashMap, HashSet, VecDeque},
+    cmp::Reverse,
+    collections::{BinaryHeap, HashMap, HashSet},
     fmt::Debug,
 };
 
+use graph::Graph;
 use nom::{
     character::complete::{char as nomchar, newline, u16 as nomu16},
     multi::separated_list1,
@@ -11,94 +13,400 @@ use nom::{
 };
 
 pub fn process_part1(input: &str) -> String {
-    let (input, bricks) = parse_input(input).unwrap();
-    assert!(input.is_empty());
-
-    let (bricks, supporting_bricks_of, bricks_supported_by) = process_bricks(bricks);
-
-    // count the ones that can be disintegrated (i.e. are not supporting anything that is not otherwise supported)
-    let disintegrateable = (0..bricks.len())
-        .filter_map(|i| {
-            if let Some(supported) = bricks_supported_by.get(&i) {
-                // supported = all bricks supported by brick i
-                // check if they all have more than 1 supporter
-                if supported.iter().all(|s| {
-                    if let Some(supporting) = supporting_bricks_of.get(s) {
-                        supporting.len() > 1
-                    } else {
-                        false
-                    }
-                }) {
-                    Some(())
-                } else {
-                    None
-                }
-            } else {
-                // brick i is not supporting any bricks
-                Some(())
-            }
-        })
+    let stack = Stack::parse(input);
+
+    // count the ones that can be disintegrated (i.e. everything on top has another supporter)
+    let disintegrateable = stack
+        .ids()
+        .filter(|&i| stack.supported(i).all(|s| stack.supporters(s).count() > 1))
         .count();
 
     disintegrateable.to_string()
 }
 
 pub fn process_part2(input: &str) -> String {
-    let (input, bricks) = parse_input(input).unwrap();
-    assert!(input.is_empty());
-
-    let (bricks, supporting_bricks_of, bricks_supported_by) = process_bricks(bricks);
-    let sum = (0..bricks.len())
-        .filter(|i| {
-            if let Some(supported) = bricks_supported_by.get(i) {
-                // supported = all bricks supported by brick i
-                // check if they all have more than 1 supporter
-                !supported.iter().all(|s| {
-                    if let Some(supporting) = supporting_bricks_of.get(s) {
-                        supporting.len() > 1
+    let stack = Stack::parse(input);
+    let dominators = stack.dominator_tree();
+    let sum = stack.ids().map(|i| dominators.falling(i)).sum::<usize>();
+    sum.to_string()
+}
+
+/// A settled tower of bricks that can be changed one brick at a time, the support relations are
+/// updated along with every change
+#[derive(Debug, Clone)]
+pub struct Stack {
+    /// Removed bricks leave a gap, so the ids of the other bricks don't change
+    bricks: Vec<Option<Brick>>,
+    space: HashMap<Point, usize>,
+    supporting_bricks_of: HashMap<usize, HashSet<usize>>,
+    bricks_supported_by: HashMap<usize, HashSet<usize>>,
+}
+
+impl Stack {
+    pub fn parse(input: &str) -> Self {
+        let (input, bricks) = parse_input(input).unwrap();
+        assert!(input.is_empty());
+        bricks.into()
+    }
+
+    /// Ids of all bricks in the stack
+    pub fn ids(&self) -> impl Iterator<Item = usize> + '_ {
+        self.bricks
+            .iter()
+            .enumerate()
+            .filter_map(|(i, b)| b.as_ref().map(|_| i))
+    }
+
+    pub fn brick(&self, id: usize) -> Option<&Brick> {
+        self.bricks.get(id).and_then(|b| b.as_ref())
+    }
+
+    /// Bricks directly below the brick
+    pub fn supporters(&self, id: usize) -> impl Iterator<Item = usize> + '_ {
+        self.supporting_bricks_of
+            .get(&id)
+            .into_iter()
+            .flatten()
+            .cloned()
+    }
+
+    /// Bricks directly on top of the brick
+    pub fn supported(&self, id: usize) -> impl Iterator<Item = usize> + '_ {
+        self.bricks_supported_by
+            .get(&id)
+            .into_iter()
+            .flatten()
+            .cloned()
+    }
+
+    /// Drops the brick onto the stack and returns its id
+    pub fn add_brick(&mut self, brick: Brick) -> usize {
+        assert!(
+            brick.cubes().all(|p| !self.space.contains_key(&p)),
+            "The new brick overlaps the stack"
+        );
+        let id = self.bricks.len();
+        self.bricks.push(None);
+        self.drop_brick(id, brick);
+        id
+    }
+
+    /// Takes the brick out and lets everything above it settle again, returns the bricks that moved
+    pub fn remove_brick(&mut self, id: usize) -> Vec<usize> {
+        let brick = self.bricks[id].take().expect("Brick is in the stack");
+        let mut loose: BinaryHeap<_> = self
+            .lift_brick(id, &brick)
+            .into_iter()
+            .map(|b| Reverse((self.bottom_of(b), b)))
+            .collect();
+        self.supporting_bricks_of.remove(&id);
+        self.bricks_supported_by.remove(&id);
+
+        // lower bricks have to settle first, so the ones above them have something to land on
+        let mut moved = vec![];
+        while let Some(Reverse((bottom, b))) = loose.pop() {
+            if bottom == 1 || !self.supporting_bricks_of[&b].is_empty() {
+                continue;
+            }
+            let brick = self.bricks[b].take().expect("Brick is in the stack");
+            self.lift_brick(b, &brick)
+                .into_iter()
+                .for_each(|above| loose.push(Reverse((self.bottom_of(above), above))));
+            self.drop_brick(b, brick);
+            moved.push(b);
+        }
+        moved.sort();
+        moved.dedup();
+        moved
+    }
+
+    /// The bricks that would fall in a chain reaction if the brick was removed, without letting
+    /// them settle
+    pub fn would_fall(&self, id: usize) -> Vec<usize> {
+        let mut fallen = HashSet::from([id]);
+        let mut candidates: BinaryHeap<_> = self
+            .supported(id)
+            .map(|b| Reverse((self.bottom_of(b), b)))
+            .collect();
+        // bricks lower down are decided first, so all supporters of a brick are known when it
+        // is popped
+        while let Some(Reverse((_, b))) = candidates.pop() {
+            if fallen.contains(&b) || !self.supporters(b).all(|s| fallen.contains(&s)) {
+                continue;
+            }
+            fallen.insert(b);
+            self.supported(b)
+                .for_each(|above| candidates.push(Reverse((self.bottom_of(above), above))));
+        }
+        fallen.remove(&id);
+        let mut fallen: Vec<_> = fallen.into_iter().collect();
+        fallen.sort();
+        fallen
+    }
+
+    /// A brick falls without another brick exactly when that brick dominates it, so the number of
+    /// bricks that fall is the size of its subtree in the dominator tree
+    pub fn dominator_tree(&self) -> DominatorTree {
+        let ground = self.bricks.len();
+        let levels = (usize::BITS - ground.leading_zeros()) as usize + 1;
+        // ancestors[k][b] is the 2^k-th dominator up from brick b, the ground dominates itself
+        let mut ancestors = vec![vec![ground; ground + 1]; levels];
+        let mut depths = vec![0; ground + 1];
+        let sorted = self
+            .support_graph()
+            .topological_sort()
+            .expect("Bricks cannot support each other in a circle");
+        for &b in sorted.iter() {
+            // all supporters come earlier in the sorted order, so they are already in the tree
+            let dominator = self
+                .supporters(b)
+                .reduce(|x, y| lowest_common_ancestor(&ancestors, &depths, x, y))
+                .unwrap_or(ground);
+            ancestors[0][b] = dominator;
+            for k in 1..levels {
+                ancestors[k][b] = ancestors[k - 1][ancestors[k - 1][b]];
+            }
+            depths[b] = depths[dominator] + 1;
+        }
+
+        let mut sizes = vec![0; ground + 1];
+        for &b in sorted.iter().rev() {
+            sizes[b] += 1;
+            sizes[ancestors[0][b]] += sizes[b];
+        }
+        let dominators = (0..ground)
+            .map(|b| Some(ancestors[0][b]).filter(|&d| d != ground))
+            .collect();
+        sizes.truncate(ground);
+        DominatorTree { dominators, sizes }
+    }
+
+    /// Wavefront OBJ with a box for every brick, the colours are added to the vertices (`v x y z r g b`)
+    /// and the tower stands up along the y axis
+    pub fn to_obj(&self, colouring: Colouring) -> String {
+        let dominators = self.dominator_tree();
+        let most_falling = self.ids().map(|i| dominators.falling(i)).max().unwrap_or(0);
+        let mut vertices = String::new();
+        let mut faces = String::new();
+        for (n, i) in self.ids().enumerate() {
+            let brick = self.brick(i).expect("Brick is in the stack");
+            let (r, g, b) = match colouring {
+                Colouring::Distinct => hsv_to_rgb((i as f64 * 0.618_034).fract(), 0.65, 0.95),
+                Colouring::Falling => {
+                    let share = dominators.falling(i) as f64 / most_falling.max(1) as f64;
+                    hsv_to_rgb((1.0 - share) * 2.0 / 3.0, 1.0, 1.0)
+                }
+            };
+            let (low, high) = brick.corners();
+            // corner c has the high x if bit 0 is set, the high y for bit 1 and the high z for bit 2
+            for c in 0..8 {
+                let pick = |bit: usize, low: u16, high: u16| {
+                    if c & bit == 0 {
+                        low as i32
                     } else {
-                        false
+                        high as i32 + 1
                     }
-                })
-            } else {
-                // brick i is not supporting any bricks
-                false
+                };
+                let (x, y, z) = (
+                    pick(1, low.x, high.x),
+                    pick(2, low.y, high.y),
+                    pick(4, low.z, high.z),
+                );
+                vertices.push_str(&format!("v {x} {z} {} {r:.3} {g:.3} {b:.3}\n", -y));
             }
-        })
-        .map(|i| {
-            let mut disintegrated = HashSet::new();
-            let mut queue = VecDeque::new();
-            queue.push_back(i);
-            while let Some(brick_to_disintegrate) = queue.pop_front() {
-                disintegrated.insert(brick_to_disintegrate);
-                if let Some(supported) = bricks_supported_by.get(&brick_to_disintegrate) {
-                    supported.iter().for_each(|sup| {
-                        // sup is supported by a disintegrated brick, should it fall?
-                        if let Some(supporting) = supporting_bricks_of.get(sup) {
-                            if supporting
-                                .iter()
-                                .filter(|&s| !disintegrated.contains(s))
-                                .count()
-                                == 0
-                            {
-                                // yes, it should
-                                queue.push_back(*sup);
-                            }
-                        }
-                    })
+            // counter-clockwise seen from outside the box
+            for face in [
+                [0, 2, 3, 1],
+                [4, 5, 7, 6],
+                [0, 1, 5, 4],
+                [2, 6, 7, 3],
+                [0, 4, 6, 2],
+                [1, 3, 7, 5],
+            ] {
+                let [a, b, c, d] = face.map(|corner| n * 8 + corner + 1);
+                faces.push_str(&format!("f {a} {b} {c} {d}\n"));
+            }
+        }
+        format!("# {} bricks\n{vertices}{faces}", self.ids().count())
+    }
+
+    /// Directed Acyclical Graph with an edge from every brick to the bricks it supports
+    fn support_graph(&self) -> Graph<usize> {
+        let mut graph = Graph::new();
+        self.ids().for_each(|i| graph.add_node(i));
+        self.bricks_supported_by
+            .iter()
+            .for_each(|(&supporter, supported)| {
+                supported
+                    .iter()
+                    .for_each(|&s| graph.add_edge(supporter, s, 1));
+            });
+        graph
+    }
+
+    fn bottom_of(&self, id: usize) -> u16 {
+        self.brick(id).expect("Brick is in the stack").bottom()
+    }
+
+    /// Lowers the brick as far as it goes and puts it in the stack
+    fn drop_brick(&mut self, id: usize, brick: Brick) {
+        let mut brick = brick;
+        while brick.cubes_below().all(|p| !self.space.contains_key(&p)) {
+            let Some(lower) = brick.one_z_lower() else {
+                break;
+            };
+            brick = lower;
+        }
+        brick.cubes().for_each(|p| {
+            self.space.insert(p, id);
+        });
+
+        let below: HashSet<usize> = brick
+            .cubes_below()
+            .filter_map(|p| self.space.get(&p))
+            .cloned()
+            .collect();
+        below.iter().for_each(|&b| {
+            self.bricks_supported_by.entry(b).or_default().insert(id);
+        });
+        self.supporting_bricks_of.insert(id, below);
+
+        // a brick dropped into a gap can end up right below another brick
+        let above: HashSet<usize> = brick
+            .cubes_above()
+            .filter_map(|p| self.space.get(&p))
+            .cloned()
+            .collect();
+        above.iter().for_each(|&b| {
+            self.supporting_bricks_of.entry(b).or_default().insert(id);
+        });
+        if !above.is_empty() {
+            self.bricks_supported_by
+                .entry(id)
+                .or_default()
+                .extend(above);
+        }
+        self.bricks[id] = Some(brick);
+    }
+
+    /// Takes the brick out of the space and the support relations, returns the bricks that were
+    /// resting on it
+    fn lift_brick(&mut self, id: usize, brick: &Brick) -> Vec<usize> {
+        brick.cubes().for_each(|p| {
+            self.space.remove(&p);
+        });
+        self.supporting_bricks_of
+            .get_mut(&id)
+            .map(std::mem::take)
+            .unwrap_or_default()
+            .into_iter()
+            .for_each(|b| {
+                if let Some(supported) = self.bricks_supported_by.get_mut(&b) {
+                    supported.remove(&id);
                 }
+            });
+        let above: Vec<usize> = self
+            .bricks_supported_by
+            .remove(&id)
+            .unwrap_or_default()
+            .into_iter()
+            .collect();
+        above.iter().for_each(|b| {
+            if let Some(supporting) = self.supporting_bricks_of.get_mut(b) {
+                supporting.remove(&id);
             }
-            disintegrated.len().saturating_sub(1)
-        })
-        .sum::<usize>();
-    sum.to_string()
+        });
+        above
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Colouring {
+    /// Every brick gets its own colour
+    Distinct,
+    /// Blue for bricks that nothing depends on, up to red for the brick that brings down the most
+    Falling,
+}
+
+/// hue, saturation and value between 0 and 1
+fn hsv_to_rgb(hue: f64, saturation: f64, value: f64) -> (f64, f64, f64) {
+    let sector = hue * 6.0;
+    let chroma = value * saturation;
+    let x = chroma * (1.0 - (sector % 2.0 - 1.0).abs());
+    let (r, g, b) = match sector as u8 {
+        0 => (chroma, x, 0.0),
+        1 => (x, chroma, 0.0),
+        2 => (0.0, chroma, x),
+        3 => (0.0, x, chroma),
+        4 => (x, 0.0, chroma),
+        _ => (chroma, 0.0, x),
+    };
+    let m = value - chroma;
+    (r + m, g + m, b + m)
+}
+
+/// Dominator tree of the support graph of a [Stack], with the ground as the root
+#[derive(Debug, Clone)]
+pub struct DominatorTree {
+    /// The closest brick that every brick depends on, None if it only depends on the ground
+    dominators: Vec<Option<usize>>,
+    /// Number of bricks in the subtree of every brick, including itself
+    sizes: Vec<usize>,
+}
+
+impl DominatorTree {
+    pub fn immediate_dominator(&self, id: usize) -> Option<usize> {
+        self.dominators[id]
+    }
+
+    /// Number of bricks that fall when the brick is removed
+    pub fn falling(&self, id: usize) -> usize {
+        assert!(self.sizes[id] > 0, "Brick is in the stack");
+        self.sizes[id] - 1
+    }
+}
+
+fn lowest_common_ancestor(ancestors: &[Vec<usize>], depths: &[usize], a: usize, b: usize) -> usize {
+    let (mut a, mut b) = if depths[a] >= depths[b] {
+        (a, b)
+    } else {
+        (b, a)
+    };
+    // first bring a up to the same depth as b
+    for k in (0..ancestors.len()).rev() {
+        if depths[a] - depths[b] >= 1 << k {
+            a = ancestors[k][a];
+        }
+    }
+    if a == b {
+        return a;
+    }
+    for k in (0..ancestors.len()).rev() {
+        if ancestors[k][a] != ancestors[k][b] {
+            a = ancestors[k][a];
+            b = ancestors[k][b];
+        }
+    }
+    ancestors[0][a]
+}
+
+impl From<Vec<Brick>> for Stack {
+    fn from(bricks: Vec<Brick>) -> Self {
+        let (bricks, space, supporting_bricks_of, bricks_supported_by) = process_bricks(bricks);
+        Self {
+            bricks: bricks.into_iter().map(Some).collect(),
+            space,
+            supporting_bricks_of,
+            bricks_supported_by,
+        }
+    }
 }
 
 #[derive(Clone, PartialEq, Eq, Hash)]
-struct Point {
-    x: u16,
-    y: u16,
-    z: u16,
+pub struct Point {
+    pub x: u16,
+    pub y: u16,
+    pub z: u16,
 }
 
 impl Point {
@@ -118,10 +426,10 @@ impl Debug for Point {
 }
 
 #[derive(Debug, Clone)]
-struct Brick(Point, Point);
+pub struct Brick(Point, Point);
 
 impl Brick {
-    fn new(point1: Point, point2: Point) -> Self {
+    pub fn new(point1: Point, point2: Point) -> Self {
         Self(point1, point2)
     }
 
@@ -132,8 +440,29 @@ impl Brick {
         Ok((input, Brick::new(point1, point2)))
     }
 
+    /// The lowest and the highest corner
+    fn corners(&self) -> (Point, Point) {
+        let Brick(a, b) = self;
+        (
+            Point {
+                x: a.x.min(b.x),
+                y: a.y.min(b.y),
+                z: a.z.min(b.z),
+            },
+            Point {
+                x: a.x.max(b.x),
+                y: a.y.max(b.y),
+                z: a.z.max(b.z),
+            },
+        )
+    }
+
+    fn bottom(&self) -> u16 {
+        self.0.z.min(self.1.z)
+    }
+
     fn one_z_lower(&self) -> Option<Self> {
-        if self.0.z.min(self.1.z) <= 1 {
+        if self.bottom() <= 1 {
             None
         } else {
             Some(Brick(
@@ -178,7 +507,7 @@ impl Brick {
     }
 
     fn bottom_cubes(&self) -> impl Iterator<Item = Point> {
-        let min_z = self.0.z.min(self.1.z);
+        let min_z = self.bottom();
         self.cubes().filter(move |p| p.z == min_z)
     }
 
@@ -189,10 +518,20 @@ impl Brick {
             z: p.z - 1,
         })
     }
+
+    fn cubes_above(&self) -> impl Iterator<Item = Point> {
+        let max_z = self.0.z.max(self.1.z);
+        self.cubes().filter(move |p| p.z == max_z).map(|p| Point {
+            x: p.x,
+            y: p.y,
+            z: p.z + 1,
+        })
+    }
 }
 
 type BrickDump = (
     Vec<Brick>,
+    HashMap<Point, usize>,
     HashMap<usize, HashSet<usize>>,
     HashMap<usize, HashSet<usize>>,
 );
@@ -246,12 +585,12 @@ fn process_bricks(bricks: Vec<Brick>) -> BrickDump {
         v.iter().for_each(|supporter| {
             bricks_supported_by
                 .entry(*supporter)
-                .or_insert_with(HashSet::new)
+                .or_default()
                 .insert(*k);
         });
     });
 
-    (bricks, supporting_bricks_of, bricks_supported_by)
+    (bricks, space, supporting_bricks_of, bricks_supported_by)
 }
 
 fn parse_input(input: &str) -> IResult<&str, Vec<Brick>> {
@@ -288,4 +627,106 @@ mod tests {
         let result = process_part2(input);
         assert_eq!(result, "7");
     }
+
+    #[test]
+    fn stack_changes() {
+        let input = "1,0,1~1,2,1
+0,0,2~2,0,2
+0,2,3~2,2,3
+0,0,4~0,2,4
+2,0,5~2,2,5
+0,1,6~2,1,6
+1,1,8~1,1,9";
+        let mut stack = Stack::parse(input);
+        assert_eq!(stack.would_fall(0), vec![1, 2, 3, 4, 5, 6]);
+        assert_eq!(stack.would_fall(5), vec![6]);
+        assert!(stack.would_fall(1).is_empty());
+
+        // without F, G drops all the way down to A
+        assert_eq!(stack.remove_brick(5), vec![6]);
+        assert_eq!(stack.brick(6).unwrap().bottom(), 2);
+        assert_eq!(stack.supporters(6).collect::<Vec<_>>(), vec![0]);
+        assert_eq!(stack.would_fall(0), vec![1, 2, 3, 4, 6]);
+
+        // dropping F again puts it on top of D, E and G
+        let (_, f) = Brick::parse("0,1,6~2,1,6").unwrap();
+        let f = stack.add_brick(f);
+        assert_eq!(f, 7);
+        assert_eq!(stack.brick(f).unwrap().bottom(), 4);
+        assert_eq!(
+            stack.supporters(f).collect::<HashSet<_>>(),
+            HashSet::from([3, 4, 6])
+        );
+        assert!(stack.would_fall(3).is_empty());
+        assert_eq!(stack.ids().count(), 7);
+
+        // F stays up without G, and a new brick in the gap ends up supporting it
+        assert!(stack.remove_brick(6).is_empty());
+        let (_, gap) = Brick::parse("1,1,2~1,1,3").unwrap();
+        let gap = stack.add_brick(gap);
+        assert_eq!(stack.brick(gap).unwrap().bottom(), 2);
+        assert_eq!(stack.supported(gap).collect::<Vec<_>>(), vec![f]);
+
+        // without A everything settles one level lower
+        let moved = stack.remove_brick(0);
+        assert_eq!(moved, vec![1, 2, 3, 4, f, gap]);
+        assert_eq!(stack.brick(f).unwrap().bottom(), 3);
+        assert!(stack.brick(0).is_none());
+
+        // the dominator tree agrees with the chain reactions after all those changes
+        let dominators = stack.dominator_tree();
+        for i in stack.ids() {
+            assert_eq!(dominators.falling(i), stack.would_fall(i).len());
+        }
+        assert_eq!(dominators.immediate_dominator(f), None);
+    }
+
+    #[test]
+    fn dominator_tree() {
+        let input = "1,0,1~1,2,1
+0,0,2~2,0,2
+0,2,3~2,2,3
+0,0,4~0,2,4
+2,0,5~2,2,5
+0,1,6~2,1,6
+1,1,8~1,1,9";
+        let stack = Stack::parse(input);
+        let dominators = stack.dominator_tree();
+        let falling: Vec<_> = stack.ids().map(|i| dominators.falling(i)).collect();
+        assert_eq!(falling, vec![6, 0, 0, 0, 0, 1, 0]);
+        assert_eq!(dominators.immediate_dominator(0), None);
+        assert_eq!(dominators.immediate_dominator(3), Some(0));
+        assert_eq!(dominators.immediate_dominator(6), Some(5));
+    }
+
+    #[test]
+    fn obj_export() {
+        let input = "1,0,1~1,2,1
+0,0,2~2,0,2
+0,2,3~2,2,3
+0,0,4~0,2,4
+2,0,5~2,2,5
+0,1,6~2,1,6
+1,1,8~1,1,9";
+        let stack = Stack::parse(input);
+        let obj = stack.to_obj(Colouring::Falling);
+        let lines: Vec<&str> = obj.lines().collect();
+        assert_eq!(lines[0], "# 7 bricks");
+        assert_eq!(lines.iter().filter(|l| l.starts_with("v ")).count(), 7 * 8);
+        assert_eq!(lines.iter().filter(|l| l.starts_with("f ")).count(), 7 * 6);
+        // A brings down everything, B doesn't bring down anything
+        assert_eq!(lines[1], "v 1 1 0 1.000 0.000 0.000");
+        assert_eq!(lines[8], "v 2 2 -3 1.000 0.000 0.000");
+        assert_eq!(lines[9], "v 0 2 0 0.000 0.000 1.000");
+        assert_eq!(lines[57], "f 1 3 4 2");
+        assert_eq!(lines.last(), Some(&"f 50 52 56 54"));
+
+        let distinct = stack.to_obj(Colouring::Distinct);
+        let colours: HashSet<&str> = distinct
+            .lines()
+            .filter(|l| l.starts_with("v "))
+            .map(|l| &l[l.len() - 17..])
+            .collect();
+        assert_eq!(colours.len(), 7);
+    }
 }
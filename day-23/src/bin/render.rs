@@ -0,0 +1,12 @@
+use answer::{render_hike, trail_graph_dot, Slopes};
+use std::fs;
+
+fn main() {
+    let file = fs::read_to_string("./input.txt").unwrap();
+    for (slopes, name) in [(Slopes::Slippery, "slippery"), (Slopes::Dry, "dry")] {
+        println!("{}\n", render_hike(&file, slopes));
+        let dot_name = format!("./trails-{name}.dot");
+        fs::write(&dot_name, trail_graph_dot(&file, slopes)).unwrap();
+        println!("Written {dot_name}\n");
+    }
+}
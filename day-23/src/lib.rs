@@ -1,8 +1,6 @@
 use std::{
-    collections::{BinaryHeap, HashMap, HashSet, VecDeque},
-    error::Error,
-    fmt::{Debug, Display},
-    hash::Hash,
+    collections::{BinaryHeap, HashMap, VecDeque},
+    sync::atomic::{AtomicU32, AtomicUsize, Ordering::Relaxed},
 };
 
 use nom::{
@@ -12,101 +10,156 @@ use nom::{
     IResult,
 };
 
+use graph::Graph;
+
 pub fn process_part1(input: &str) -> String {
     let (input, tiles) = parse_input(input).unwrap();
     assert!(input.is_empty());
+    let (_, length) = longest_hike(&tiles, Slopes::Slippery);
+    length.to_string()
+}
 
-    let height = tiles.len() - 1;
+pub fn process_part2(input: &str) -> String {
+    let (input, tiles) = parse_input(input).unwrap();
+    assert!(input.is_empty());
+    let (_, length) = longest_hike(&dry(tiles), Slopes::Dry);
+    length.to_string()
+}
 
-    let start_x = tiles[0]
-        .iter()
-        .enumerate()
-        .fold(None, |acc, (i, t)| match t {
-            &Tile::Path => {
-                if acc.is_some() {
-                    panic!("There should be only one path tile in the top row");
-                }
-                Some(i)
-            }
-            _ => acc,
-        })
-        .expect("There should be at least one path tile in the top row");
-    let end_x = tiles[height]
-        .iter()
-        .enumerate()
-        .fold(None, |acc, (i, t)| match t {
-            &Tile::Path => {
-                if acc.is_some() {
-                    panic!("There should be only one path tile in the bottom row");
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Slopes {
+    /// Slopes can only be walked down, like in part 1
+    Slippery,
+    /// Slopes are just paths, like in part 2
+    Dry,
+}
+
+/// Returns the compressed graph of the trails in DOT format, with the lengths as edge labels.
+/// With slippery slopes the graph is directed, with dry slopes it's undirected.
+pub fn trail_graph_dot(input: &str, slopes: Slopes) -> String {
+    let (input, tiles) = parse_input(input).unwrap();
+    assert!(input.is_empty());
+    let (start, end) = start_and_end(&tiles);
+    let name = |p: &Point| format!("\"{},{}\"", p.x, p.y);
+    let mut lines = match slopes {
+        Slopes::Slippery => {
+            let graph = derive_graph_and_distances(&tiles, start, end);
+            let mut edges: Vec<String> = graph
+                .edges()
+                .map(|(from, to, length)| {
+                    format!("    {} -> {} [label={}];", name(from), name(to), length)
+                })
+                .collect();
+            edges.sort();
+            edges.dedup();
+            edges.insert(0, "digraph trails {".to_string());
+            edges
+        }
+        Slopes::Dry => {
+            let tiles = dry(tiles);
+            let graph = JunctionGraph::new(
+                &derive_graph_and_distances_part_2(&tiles, start, end),
+                start,
+                end,
+            );
+            let mut edges = vec!["graph trails {".to_string()];
+            for (from, neighbours) in graph.edges.iter().enumerate() {
+                for &(to, length) in neighbours.iter().filter(|&&(to, _)| from < to) {
+                    edges.push(format!(
+                        "    {} -- {} [label={}];",
+                        name(&graph.points[from]),
+                        name(&graph.points[to]),
+                        length
+                    ));
                 }
-                Some(i)
             }
-            _ => acc,
-        })
-        .expect("There should be at least one path tile in the bottom row");
-
-    // turn the forest into a Directed Acyclical Graph
-    let (graph, mut edges) = derive_graph_and_distances(
-        &tiles,
-        Point { x: start_x, y: 0 },
-        Point {
-            x: end_x,
-            y: height,
-        },
-    );
-    // do a topological sort
-    let state = State::from_graph(graph);
-    let sorted = topological_sort(state).expect("forest should be sortable");
-    // find the shortest path (using negative edge weights)
-    edges.values_mut().for_each(|v| {
-        v.iter_mut().for_each(|e| {
-            *e = e.negate();
-        })
-    });
-    let mut path_lengths: HashMap<Point, i32> = sorted.iter().map(|p| (*p, i32::MAX)).collect();
-    *path_lengths
-        .get_mut(&Point { x: start_x, y: 0 })
-        .expect("start point should be in forest") = 0;
-    sorted.iter().take(sorted.len() - 1).for_each(|p| {
-        edges
-            .get(p)
-            .expect("Point must have at least one edge")
-            .iter()
-            .for_each(|edge| {
-                let current_length = *path_lengths.get(&edge.from).expect("edge must exist");
-                path_lengths
-                    .entry(edge.to)
-                    .and_modify(|length| *length = (*length).min(edge.length + current_length));
-            })
-    });
-    let longest_path = path_lengths
-        .get(&Point {
-            x: end_x,
-            y: height,
-        })
-        .expect("Path to the end must exist");
-
-    // negate the result
-    (-longest_path).to_string()
+            edges
+        }
+    };
+    lines.push("}\n".to_string());
+    lines.join("\n")
 }
 
-pub fn process_part2(input: &str) -> String {
-    // it's not pretty and it takes its sweet time, but it gets there
+/// Returns the forest with the longest hike drawn on it, like in the puzzle description
+pub fn render_hike(input: &str, slopes: Slopes) -> String {
     let (input, tiles) = parse_input(input).unwrap();
     assert!(input.is_empty());
-    // get rid of the slopes
-    let tiles: Vec<Vec<Tile>> = tiles
-        .into_iter()
-        .map(|row| {
-            row.into_iter()
-                .map(|tile| match tile {
-                    Tile::Slope(_) => Tile::Path,
-                    other => other,
+    let mut map: Vec<Vec<char>> = tiles
+        .iter()
+        .map(|row| row.iter().map(|tile| tile.symbol()).collect())
+        .collect();
+    let tiles = match slopes {
+        Slopes::Slippery => tiles,
+        Slopes::Dry => dry(tiles),
+    };
+    let (hike, _) = longest_hike(&tiles, slopes);
+    for point in hike.iter().skip(1) {
+        map[point.y][point.x] = 'O';
+    }
+    map[hike[0].y][hike[0].x] = 'S';
+
+    map.into_iter()
+        .map(|row| row.into_iter().collect::<String>())
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// Every tile along the longest hike, and its length
+fn longest_hike(tiles: &[Vec<Tile>], slopes: Slopes) -> (Vec<Point>, usize) {
+    let (start, end) = start_and_end(tiles);
+    // (from, to, length) for every trail the hike takes
+    let legs: Vec<(Point, Point, usize)> = match slopes {
+        Slopes::Slippery => {
+            // turn the forest into a Directed Acyclical Graph
+            let graph = derive_graph_and_distances(tiles, start, end);
+            let longest_path = graph
+                .longest_path(&start, &end)
+                .expect("forest should be sortable and the end reachable");
+            longest_path
+                .nodes
+                .windows(2)
+                .map(|leg| {
+                    // slopes reached from several others are walked from more than once, so
+                    // the same trail can be in the graph several times
+                    let length = graph
+                        .successors(&leg[0])
+                        .filter(|&(to, _)| *to == leg[1])
+                        .map(|(_, length)| length)
+                        .max()
+                        .expect("Consecutive nodes are connected");
+                    (leg[0], leg[1], length as usize)
                 })
                 .collect()
-        })
-        .collect();
+        }
+        Slopes::Dry => {
+            let distances = derive_graph_and_distances_part_2(tiles, start, end);
+            let graph = JunctionGraph::new(&distances, start, end);
+            let threads = std::thread::available_parallelism().map_or(1, |n| n.get());
+            graph
+                .longest_route(threads)
+                .expect("There should be a path to the end")
+        }
+    };
 
+    let trails = Trails {
+        tiles,
+        slopes,
+        no_visits: vec![vec![false; tiles[0].len()]; tiles.len()],
+    };
+    let mut hike = vec![start];
+    for &(from, to, length) in legs.iter() {
+        hike.extend(trails.trail(from, to, length).into_iter().skip(1));
+    }
+    let length = legs.iter().map(|&(_, _, length)| length).sum();
+    assert_eq!(
+        hike.len() - 1,
+        length,
+        "Every step of the hike is on a trail"
+    );
+    (hike, length)
+}
+
+fn start_and_end(tiles: &[Vec<Tile>]) -> (Point, Point) {
     let height = tiles.len() - 1;
 
     let start_x = tiles[0]
@@ -136,32 +189,31 @@ pub fn process_part2(input: &str) -> String {
         })
         .expect("There should be at least one path tile in the bottom row");
 
-    let (graph, distances) = derive_graph_and_distances_part_2(
-        &tiles,
+    (
         Point { x: start_x, y: 0 },
         Point {
             x: end_x,
             y: height,
         },
-    );
-
-    let visited = HashSet::new();
-    let longest = longest_path(
-        &graph,
-        &distances,
-        Point { x: start_x, y: 0 },
-        Point {
-            x: end_x,
-            y: tiles.len() - 1,
-        },
-        0,
-        visited,
-    );
+    )
+}
 
-    longest.to_string()
+/// Gets rid of the slopes
+fn dry(tiles: Vec<Vec<Tile>>) -> Vec<Vec<Tile>> {
+    tiles
+        .into_iter()
+        .map(|row| {
+            row.into_iter()
+                .map(|tile| match tile {
+                    Tile::Slope(_) => Tile::Path,
+                    other => other,
+                })
+                .collect()
+        })
+        .collect()
 }
 
-#[derive(Debug, PartialEq, Eq, PartialOrd, Hash, Clone, Copy)]
+#[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Clone, Copy)]
 struct Point {
     x: usize,
     y: usize,
@@ -216,105 +268,15 @@ impl Tile {
         let (input, slope) = Slope::parse(input)?;
         Ok((input, Self::Slope(slope)))
     }
-}
-
-type Graph<T> = HashMap<T, HashSet<T>>;
-
-#[derive(Debug)]
-struct State<T>
-where
-    T: Copy + Eq + Hash,
-{
-    dependents: Graph<T>,
-    dependencies: Graph<T>,
-    no_deps: VecDeque<T>,
-}
-
-#[allow(dead_code)]
-impl<T> State<T>
-where
-    T: Copy + Eq + Hash + Debug,
-{
-    fn from_graph(graph: Graph<T>) -> Self {
-        let dependents = graph;
-
-        let mut dependencies = HashMap::new();
-        dependents.iter().for_each(|(dependency, dependents)| {
-            dependents.iter().for_each(|dependent| {
-                dependencies
-                    .entry(*dependent)
-                    .or_insert_with(HashSet::new)
-                    .insert(*dependency);
-            });
-        });
-        let no_deps = dependencies
-            .iter()
-            .flat_map(|(_, v)| v.iter())
-            .filter(|&k| !dependencies.contains_key(k))
-            .cloned()
-            .collect();
-
-        Self {
-            dependents,
-            dependencies,
-            no_deps,
-        }
-    }
-
-    fn get_dependents(&self, dependency: &T) -> Option<&HashSet<T>> {
-        self.dependents.get(dependency)
-    }
-
-    fn is_resolved(&self) -> bool {
-        self.dependencies.is_empty()
-    }
-
-    fn resolve(&mut self, dependent: &T, dependency: &T) {
-        // depedency = from, dependent = to
-        if let Some(dpndncies) = self.dependencies.get_mut(dependent) {
-            dpndncies.remove(dependency);
-            if dpndncies.is_empty() {
-                self.dependencies.remove(dependent);
-                self.no_deps.push_back(*dependent);
-            }
-        }
-    }
-
-    fn unresolved(&self) -> impl Iterator<Item = &T> {
-        self.dependencies.keys()
-    }
-}
-
-#[derive(Debug)]
-enum SortingError {
-    GraphIsCyclical,
-}
 
-impl Display for SortingError {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    fn symbol(&self) -> char {
         match self {
-            SortingError::GraphIsCyclical => {
-                f.write_str("Graph is cyclical, topological sort cannot be completed")
-            }
-        }
-    }
-}
-
-impl Error for SortingError {}
-
-#[derive(Debug, PartialEq, Eq, Hash)]
-struct Edge {
-    from: Point,
-    to: Point,
-    length: i32,
-}
-
-impl Edge {
-    fn negate(&self) -> Self {
-        Self {
-            from: self.from,
-            to: self.to,
-            length: -self.length,
+            Self::Path => '.',
+            Self::Forest => '#',
+            Self::Slope(Slope::North) => '^',
+            Self::Slope(Slope::South) => 'v',
+            Self::Slope(Slope::East) => '>',
+            Self::Slope(Slope::West) => '<',
         }
     }
 }
@@ -400,26 +362,85 @@ impl Node {
 
 impl PartialOrd for Node {
     fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
-        match other.value.partial_cmp(&self.value) {
-            Some(core::cmp::Ordering::Equal) => {}
-            ord => return ord,
-        }
-        other.point.partial_cmp(&self.point)
+        Some(self.cmp(other))
     }
 }
 
 impl Ord for Node {
     fn cmp(&self, other: &Self) -> std::cmp::Ordering {
-        self.partial_cmp(other).expect("self.value implements Ord")
+        other
+            .value
+            .cmp(&self.value)
+            .then_with(|| other.point.cmp(&self.point))
+    }
+}
+
+/// The trails one tile at a time from one waypoint to the next: slopes when they're slippery,
+/// junctions otherwise
+struct Trails<'a> {
+    tiles: &'a [Vec<Tile>],
+    slopes: Slopes,
+    no_visits: Vec<Vec<bool>>,
+}
+
+impl Trails<'_> {
+    fn is_waypoint(&self, point: Point) -> bool {
+        match self.slopes {
+            Slopes::Slippery => matches!(self.tiles[point.y][point.x], Tile::Slope(_)),
+            Slopes::Dry => {
+                let node = Node { point, value: 0 };
+                node.neighbours(self.tiles, &self.no_visits).len() > 2
+            }
+        }
+    }
+
+    /// The tiles of a trail of exactly `length` steps between the waypoints, both included
+    fn trail(&self, from: Point, to: Point, length: usize) -> Vec<Point> {
+        let mut visited = self.no_visits.clone();
+        let mut trail = vec![from];
+        assert!(
+            self.follow(to, length, &mut visited, &mut trail),
+            "There should be a trail of {length} from {from:?} to {to:?}"
+        );
+        trail
+    }
+
+    fn follow(
+        &self,
+        to: Point,
+        length: usize,
+        visited: &mut [Vec<bool>],
+        trail: &mut Vec<Point>,
+    ) -> bool {
+        let point = *trail.last().expect("The trail starts somewhere");
+        let steps = trail.len() - 1;
+        if point == to {
+            return steps == length;
+        }
+        // trails end at the next waypoint
+        if steps == length || (steps > 0 && self.is_waypoint(point)) {
+            return false;
+        }
+        visited[point.y][point.x] = true;
+        let node = Node { point, value: 0 };
+        for next in node.neighbours(self.tiles, visited) {
+            trail.push(next.point);
+            if self.follow(to, length, visited, trail) {
+                return true;
+            }
+            trail.pop();
+        }
+        visited[point.y][point.x] = false;
+        false
     }
 }
 
 #[allow(dead_code)]
 fn print_graph(graph: &Graph<Point>) {
-    graph.iter().for_each(|(k, v)| {
+    graph.nodes().for_each(|k| {
         print!("({}, {}) -> [", k.x, k.y);
-        let mut it = v.iter().peekable();
-        while let Some(p) = it.next() {
+        let mut it = graph.successors(k).peekable();
+        while let Some((p, _)) = it.next() {
             print!("({}, {})", p.x, p.y);
             if it.peek().is_some() {
                 print!(", ");
@@ -429,83 +450,69 @@ fn print_graph(graph: &Graph<Point>) {
     })
 }
 
-fn reset_visited(visited: &mut [Vec<bool>]) {
-    visited.iter_mut().for_each(|row| {
-        row.iter_mut().for_each(|v| {
-            *v = false;
-        })
-    })
-}
-
-fn derive_graph_and_distances(
-    tiles: &[Vec<Tile>],
-    start: Point,
-    end: Point,
-) -> (Graph<Point>, HashMap<Point, Vec<Edge>>) {
-    let width = tiles[0].len();
-    let height = tiles.len();
+fn derive_graph_and_distances(tiles: &[Vec<Tile>], start: Point, end: Point) -> Graph<Point> {
+    let no_visits = vec![vec![false; tiles[0].len()]; tiles.len()];
     let mut vertex_queue = VecDeque::new();
     vertex_queue.push_back(start);
-    let mut edges = HashMap::new();
-    let mut visited = vec![vec![false; width]; height];
-    let mut dijkstra_queue = BinaryHeap::new();
+    let mut graph = Graph::new();
     while let Some(start_point) = vertex_queue.pop_front() {
-        let Point { x, y } = start_point;
-        visited[y][x] = true;
-        let start_node = Node {
-            point: start_point,
-            value: 0,
+        let downhill = Downhill {
+            tiles,
+            from: start_point,
+            end,
+            no_visits: &no_visits,
         };
-        assert!(dijkstra_queue.is_empty());
-        dijkstra_queue.push(start_node);
-        reset_visited(visited.as_mut_slice());
-        while let Some(node) = dijkstra_queue.pop() {
-            visited[node.point.y][node.point.x] = true;
-            for neighbour in node.neighbours(tiles, &visited) {
-                if neighbour.point == end {
-                    edges
-                        .entry(start_point)
-                        .or_insert_with(Vec::new)
-                        .push(Edge {
-                            from: start_point,
-                            to: end,
-                            length: neighbour.value as i32,
-                        })
-                }
-                match tiles[neighbour.point.y][neighbour.point.x] {
-                    Tile::Path => dijkstra_queue.push(neighbour),
-                    Tile::Forest => unreachable!("Node.neighbours should not return Forest tiles"),
-                    Tile::Slope(_) => {
-                        vertex_queue.push_back(neighbour.point);
-                        edges
-                            .entry(start_point)
-                            .or_insert_with(Vec::new)
-                            .push(Edge {
-                                from: start_point,
-                                to: neighbour.point,
-                                length: neighbour.value as i32,
-                            });
-                    }
-                }
+        let mut distances: Vec<_> = search::distances(&downhill, [start_point])
+            .into_iter()
+            .filter(|&(point, _)| point != start_point && downhill.is_waypoint(point))
+            .collect();
+        // keep the graph the same from run to run
+        distances.sort_unstable();
+        for (point, distance) in distances {
+            if point != end {
+                vertex_queue.push_back(point);
             }
+            graph.add_edge(start_point, point, distance as i64);
         }
     }
 
-    let mut graph = HashMap::new();
-    edges.iter().for_each(|(_, es)| {
-        es.iter().for_each(|e| {
-            add_edge(&mut graph, e.from, e.to);
-        })
-    });
+    graph
+}
+
+/// Movement rules for walking from one slope down to the next ones, or to the end
+struct Downhill<'a> {
+    tiles: &'a [Vec<Tile>],
+    from: Point,
+    end: Point,
+    no_visits: &'a [Vec<bool>],
+}
+
+impl Downhill<'_> {
+    fn is_waypoint(&self, point: Point) -> bool {
+        point == self.end || matches!(self.tiles[point.y][point.x], Tile::Slope(_))
+    }
+}
+
+impl search::Movement for Downhill<'_> {
+    type State = Point;
 
-    (graph, edges)
+    fn neighbours(&self, &point: &Point) -> Vec<(Point, usize)> {
+        if point != self.from && self.is_waypoint(point) {
+            return Vec::new(); // the walk ends at the first waypoint
+        }
+        let node = Node { point, value: 0 };
+        node.neighbours(self.tiles, self.no_visits)
+            .into_iter()
+            .map(|neighbour| (neighbour.point, 1))
+            .collect()
+    }
 }
 
 fn derive_graph_and_distances_part_2(
     tiles: &[Vec<Tile>],
     start: Point,
     end: Point,
-) -> (Graph<Point>, HashMap<(Point, Point), i32>) {
+) -> HashMap<(Point, Point), i32> {
     // dijkstra but create node when at split
     let width = tiles[0].len();
     let height = tiles.len();
@@ -546,103 +553,263 @@ fn derive_graph_and_distances_part_2(
         }
     }
 
-    let mut graph = HashMap::new();
     edges
-        .keys()
-        .for_each(|(from, to)| add_edge(&mut graph, *from, *to));
-
-    (graph, edges)
-}
-
-fn add_edge<T>(graph: &mut Graph<T>, from: T, to: T)
-where
-    T: Copy + Eq + Hash,
-{
-    graph.entry(from).or_insert_with(HashSet::new).insert(to);
-}
-
-fn topological_sort<T>(state: State<T>) -> Result<Vec<T>, SortingError>
-where
-    T: Copy + Eq + Hash + Debug,
-{
-    let mut sorted = Vec::new();
-    let mut state = state;
-    while let Some(node) = state.no_deps.pop_front() {
-        sorted.push(node);
-        if let Some(dependents) = state.dependents.get(&node) {
-            // which node depend on this node?
-            for dependent in dependents.clone() {
-                state.resolve(&dependent, &node)
+}
+
+/// The graph of junctions in the forest with the nodes numbered 0.., so that a set of visited
+/// nodes fits in a u64
+#[derive(Debug)]
+struct JunctionGraph {
+    points: Vec<Point>,
+    /// (neighbour, distance) for every node
+    edges: Vec<Vec<(usize, u32)>>,
+    /// longest edge into every node, no path can enter a node over a longer edge
+    longest_edge: Vec<u32>,
+    start: usize,
+    end: usize,
+}
+
+impl JunctionGraph {
+    fn new(distances: &HashMap<(Point, Point), i32>, start: Point, end: Point) -> Self {
+        let mut neighbours: HashMap<Point, HashMap<Point, u32>> = HashMap::new();
+        for (&(from, to), &distance) in distances.iter() {
+            if from != to {
+                neighbours
+                    .entry(from)
+                    .or_default()
+                    .insert(to, distance as u32);
             }
         }
+        // trails that were walked from both ends are split up by a point with only two
+        // neighbours, every one of those costs a bit of the u64 and a level of the search
+        // junctions can end up with two neighbours too when their parallel trails are joined,
+        // so only the points that start out with two are left out
+        let mut points: Vec<Point> = neighbours
+            .iter()
+            .filter(|&(&point, to)| point != start && point != end && to.len() == 2)
+            .map(|(&point, _)| point)
+            .collect();
+        points.sort_by_key(|p| (p.y, p.x));
+        for point in points {
+            if neighbours[&point].len() != 2 {
+                continue; // both neighbours have become the same one
+            }
+            let joined: Vec<(Point, u32)> = neighbours
+                .remove(&point)
+                .expect("point is in the graph")
+                .into_iter()
+                .collect();
+            let distance = joined[0].1 + joined[1].1;
+            for (from, to) in [(joined[0].0, joined[1].0), (joined[1].0, joined[0].0)] {
+                let from = neighbours.get_mut(&from).expect("edges go both ways");
+                from.remove(&point);
+                // of two trails between the same junctions, a hike can only ever use the longest
+                from.entry(to)
+                    .and_modify(|d| *d = (*d).max(distance))
+                    .or_insert(distance);
+            }
+        }
+
+        let mut points: Vec<Point> = neighbours.keys().copied().collect();
+        points.sort_by_key(|p| (p.y, p.x));
+        assert!(
+            points.len() <= u64::BITS as usize,
+            "Too many junctions to keep track of in a u64"
+        );
+        let index: HashMap<Point, usize> =
+            points.iter().enumerate().map(|(i, &p)| (p, i)).collect();
+
+        let mut edges = vec![Vec::new(); points.len()];
+        for (from, to_distances) in neighbours.iter() {
+            for (to, &distance) in to_distances.iter() {
+                edges[index[from]].push((index[to], distance));
+            }
+        }
+        edges.iter_mut().for_each(|e| e.sort());
+        let longest_edge = edges
+            .iter()
+            .map(|e| e.iter().map(|&(_, distance)| distance).max().unwrap_or(0))
+            .collect();
+
+        Self {
+            start: index[&start],
+            end: index[&end],
+            points,
+            edges,
+            longest_edge,
+        }
     }
-    if !state.is_resolved() {
-        Err(SortingError::GraphIsCyclical)
-    } else {
-        Ok(sorted)
-    }
-}
 
-fn longest_path<T>(
-    graph: &Graph<T>,
-    distances: &HashMap<(T, T), i32>,
-    start: T,
-    end: T,
-    distance_so_far: i32,
-    visited: HashSet<T>,
-) -> i32
-where
-    T: Eq + Copy + Hash,
-{
-    fn possible_next_steps<'a, 'b, T>(
-        graph: &'a Graph<T>,
-        node: T,
-        visited: &'b HashSet<T>,
-    ) -> impl Iterator<Item = &'b T>
-    where
-        T: Eq + Copy + Hash,
-        'a: 'b,
-    {
-        if let Some(v) = graph.get(&node) {
-            v.iter().filter(|&n| !visited.contains(n))
+    /// Length of the longest path from start to end that doesn't visit any junction twice.
+    /// With more than one thread, the paths of the first few steps are divided over the threads.
+    fn longest_path(&self, threads: usize) -> Option<u32> {
+        let (search, start) = self.search();
+        let mut best = 0;
+        if threads <= 1 {
+            search.dfs(start, &mut best);
         } else {
-            unreachable!("node must exist in graph")
+            let prefixes = search.prefixes(start, threads * 8);
+            let next = AtomicUsize::new(0);
+            let shared_best = AtomicU32::new(0);
+            std::thread::scope(|scope| {
+                for _ in 0..threads {
+                    scope.spawn(|| {
+                        while let Some(&prefix) = prefixes.get(next.fetch_add(1, Relaxed)) {
+                            let mut best = shared_best.load(Relaxed);
+                            search.dfs(prefix, &mut best);
+                            shared_best.fetch_max(best, Relaxed);
+                        }
+                    });
+                }
+            });
+            best = shared_best.into_inner();
         }
+        // every path has a length, so 0 means we haven't found one
+        (best > 0).then_some(best)
     }
 
-    if let Some(&distance) = distances.get(&(start, end)) {
-        distance_so_far + distance
-    } else {
-        let mut next_visited = visited.clone();
-        next_visited.insert(start);
-        possible_next_steps(graph, start, &visited)
-            .map(|&next_node| {
-                let step = *distances
-                    .get(&(start, next_node))
-                    .expect("this is definitely an edge");
-                longest_path(
-                    graph,
-                    distances,
-                    next_node,
-                    end,
-                    distance_so_far + step,
-                    next_visited.clone(),
-                )
+    /// (from, to, length) for every edge along the longest path
+    fn longest_route(&self, threads: usize) -> Option<Vec<(Point, Point, usize)>> {
+        let length = self.longest_path(threads)?;
+        // now that we know the length, finding a path that long is quick
+        let (search, start) = self.search();
+        let mut route = Vec::new();
+        assert!(search.route(start, length, &mut route));
+        if search.target != self.end {
+            route.push(self.end);
+        }
+        let legs = route
+            .windows(2)
+            .map(|leg| {
+                let (_, length) = self.edges[leg[0]]
+                    .iter()
+                    .find(|&&(to, _)| to == leg[1])
+                    .expect("Consecutive nodes are connected");
+                (self.points[leg[0]], self.points[leg[1]], *length as usize)
             })
-            .max()
-            .or(Some(0))
-            .expect("duh")
+            .collect();
+        Some(legs)
+    }
+
+    fn search(&self) -> (Search<'_>, Prefix) {
+        // once we're at the only junction next to the end, we have to go to the end
+        let (target, target_distance) = match self.edges[self.end].as_slice() {
+            &[(neighbour, distance)] => (neighbour, distance),
+            _ => (self.end, 0),
+        };
+        let search = Search {
+            graph: self,
+            target,
+            target_distance,
+        };
+        let remaining = self
+            .longest_edge
+            .iter()
+            .enumerate()
+            .filter(|&(node, _)| node != self.start)
+            .map(|(_, &distance)| distance)
+            .sum();
+        let start = Prefix {
+            node: self.start,
+            visited: 1 << self.start,
+            length: 0,
+            remaining,
+        };
+        (search, start)
+    }
+}
+
+/// A partial path through the junction graph
+#[derive(Debug, Clone, Copy)]
+struct Prefix {
+    node: usize,
+    visited: u64,
+    length: u32,
+    /// upper bound for the length still to come
+    remaining: u32,
+}
+
+struct Search<'a> {
+    graph: &'a JunctionGraph,
+    target: usize,
+    target_distance: u32,
+}
+
+impl Search<'_> {
+    fn next_steps(&self, prefix: Prefix) -> impl Iterator<Item = Prefix> + '_ {
+        self.graph.edges[prefix.node]
+            .iter()
+            .filter(move |&&(next, _)| prefix.visited & (1 << next) == 0)
+            .map(move |&(next, distance)| Prefix {
+                node: next,
+                visited: prefix.visited | (1 << next),
+                length: prefix.length + distance,
+                remaining: prefix.remaining - self.graph.longest_edge[next],
+            })
+    }
+
+    fn dfs(&self, prefix: Prefix, best: &mut u32) {
+        if prefix.node == self.target {
+            *best = (*best).max(prefix.length + self.target_distance);
+            return;
+        }
+        if prefix.length + prefix.remaining <= *best {
+            return; // even taking the longest edge into every junction left won't beat the best
+        }
+        for next in self.next_steps(prefix) {
+            self.dfs(next, best);
+        }
+    }
+
+    /// Finds a path of exactly `length` and puts the nodes along it in `route`
+    fn route(&self, prefix: Prefix, length: u32, route: &mut Vec<usize>) -> bool {
+        route.push(prefix.node);
+        if prefix.node == self.target {
+            if prefix.length + self.target_distance == length {
+                return true;
+            }
+        } else if prefix.length + prefix.remaining >= length
+            && self
+                .next_steps(prefix)
+                .any(|next| self.route(next, length, route))
+        {
+            return true;
+        }
+        route.pop();
+        false
+    }
+
+    /// Expands the paths from `start` step by step until there are at least `count` of them
+    fn prefixes(&self, start: Prefix, count: usize) -> Vec<Prefix> {
+        let mut prefixes = vec![start];
+        while prefixes.len() < count {
+            let expanded: Vec<Prefix> = prefixes
+                .iter()
+                .flat_map(|&prefix| {
+                    if prefix.node == self.target {
+                        vec![prefix]
+                    } else {
+                        self.next_steps(prefix).collect()
+                    }
+                })
+                .collect();
+            if expanded.len() <= prefixes.len() {
+                break; // not branching out anymore
+            }
+            prefixes = expanded;
+        }
+        prefixes
     }
 }
 
 type Line<'a> = Vec<Tile>;
 
-fn parse_input(input: &str) -> IResult<&str, Vec<Line>> {
+fn parse_input(input: &str) -> IResult<&str, Vec<Line<'_>>> {
     let (input, lines) = separated_list1(newline, parse_line)(input)?;
     Ok((input, lines))
 }
 
-fn parse_line(input: &str) -> IResult<&str, Line> {
+fn parse_line(input: &str) -> IResult<&str, Line<'_>> {
     let (input, line) = many1(Tile::parse)(input)?;
     Ok((input, line))
 }
@@ -708,4 +875,155 @@ mod tests {
         let result = process_part2(input);
         assert_eq!(result, "154");
     }
+
+    #[test]
+    fn longest_path_with_threads() {
+        let input = "#.#####################
+#.......#########...###
+#######.#########.#.###
+###.....#.>.>.###.#.###
+###v#####.#v#.###.#.###
+###.>...#.#.#.....#...#
+###v###.#.#.#########.#
+###...#.#.#.......#...#
+#####.#.#.#######.#.###
+#.....#.#.#.......#...#
+#.#####.#.#.#########v#
+#.#...#...#...###...>.#
+#.#.#v#######v###.###v#
+#...#.>.#...>.>.#.###.#
+#####v#.#.###v#.#.###.#
+#.....#...#...#.#.#...#
+#.#########.###.#.#.###
+#...###...#...#...#.###
+###.###.#.###v#####v###
+#...#...#.#.>.>.#.>.###
+#.###.###.#.###.#.#v###
+#.....###...###...#...#
+#####################.#";
+        let tiles = dry(parse_input(input).unwrap().1);
+        let (start, end) = start_and_end(&tiles);
+        let distances = derive_graph_and_distances_part_2(&tiles, start, end);
+        let graph = JunctionGraph::new(&distances, start, end);
+        for threads in 1..=4 {
+            assert_eq!(graph.longest_path(threads), Some(154));
+        }
+    }
+
+    #[test]
+    fn junctions_only() {
+        // two trails between the junctions at (1, 2) and (5, 10), 20 and 12 long
+        let maze = "#.#########
+#.#########
+#.....#####
+#.###.#####
+#.###.#####
+#.###.....#
+#.#######.#
+#.#######.#
+#...###...#
+###.###.###
+###.....###
+#####.#####";
+        let tiles = dry(parse_input(maze).unwrap().1);
+        let (start, end) = start_and_end(&tiles);
+        let distances = derive_graph_and_distances_part_2(&tiles, start, end);
+        // the short trail was walked from both ends and got split where they met
+        assert_eq!(
+            distances.get(&(Point { x: 1, y: 2 }, Point { x: 4, y: 10 })),
+            Some(&11)
+        );
+        let graph = JunctionGraph::new(&distances, start, end);
+        assert_eq!(
+            graph.points,
+            vec![start, Point { x: 1, y: 2 }, Point { x: 5, y: 10 }, end]
+        );
+        // only the long trail is kept
+        assert_eq!(graph.edges[1], vec![(0, 2), (2, 20)]);
+        assert_eq!(graph.longest_path(1), Some(23));
+    }
+
+    #[test]
+    fn render() {
+        let input = "#.#####################
+#.......#########...###
+#######.#########.#.###
+###.....#.>.>.###.#.###
+###v#####.#v#.###.#.###
+###.>...#.#.#.....#...#
+###v###.#.#.#########.#
+###...#.#.#.......#...#
+#####.#.#.#######.#.###
+#.....#.#.#.......#...#
+#.#####.#.#.#########v#
+#.#...#...#...###...>.#
+#.#.#v#######v###.###v#
+#...#.>.#...>.>.#.###.#
+#####v#.#.###v#.#.###.#
+#.....#...#...#.#.#...#
+#.#########.###.#.#.###
+#...###...#...#...#.###
+###.###.#.###v#####v###
+#...#...#.#.>.>.#.>.###
+#.###.###.#.###.#.#v###
+#.....###...###...#...#
+#####################.#";
+        let result = render_hike(input, Slopes::Slippery);
+        assert_eq!(
+            result,
+            "#S#####################
+#OOOOOOO#########...###
+#######O#########.#.###
+###OOOOO#OOO>.###.#.###
+###O#####O#O#.###.#.###
+###OOOOO#O#O#.....#...#
+###v###O#O#O#########.#
+###...#O#O#OOOOOOO#...#
+#####.#O#O#######O#.###
+#.....#O#O#OOOOOOO#...#
+#.#####O#O#O#########v#
+#.#...#OOO#OOO###OOOOO#
+#.#.#v#######O###O###O#
+#...#.>.#...>OOO#O###O#
+#####v#.#.###v#O#O###O#
+#.....#...#...#O#O#OOO#
+#.#########.###O#O#O###
+#...###...#...#OOO#O###
+###.###.#.###v#####O###
+#...#...#.#.>.>.#.>O###
+#.###.###.#.###.#.#O###
+#.....###...###...#OOO#
+#####################O#"
+        );
+        // the drawn hike is the one that was counted
+        let result = render_hike(input, Slopes::Dry);
+        assert_eq!(result.matches('O').count(), 154);
+        let maze = "#.#########
+#.#########
+#.....#####
+#.###.#####
+#.###.#####
+#.###.....#
+#.#######.#
+#.#######.#
+#...###...#
+###.###.###
+###.....###
+#####.#####";
+        // of the two trails between the junctions, the hike takes the long way round
+        let (hike, length) = longest_hike(&dry(parse_input(maze).unwrap().1), Slopes::Dry);
+        assert_eq!(length, 23);
+        assert_eq!(hike.len(), 24);
+        assert!(hike.contains(&Point { x: 9, y: 5 }));
+        assert_eq!(render_hike(maze, Slopes::Dry).matches('O').count(), 23);
+
+        let dot = trail_graph_dot(input, Slopes::Slippery);
+        assert!(dot.starts_with("digraph trails {"));
+        assert!(dot.contains("\"1,0\" -> \"3,4\" [label=14];"));
+        let dot = trail_graph_dot(input, Slopes::Dry);
+        assert!(dot.starts_with("graph trails {"));
+        assert!(dot.contains("\"1,0\" -- \"3,5\" [label=15];"));
+        // every junction in the example has three or four trails
+        assert_eq!(dot.matches(" -- ").count(), 12);
+    }
 }
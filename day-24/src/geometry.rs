@@ -0,0 +1,272 @@
+//! Exact geometry of straight lines through 3D space. Everything is done with whole numbers, and
+//! times are fractions.
+use std::ops::{Add, AddAssign, Mul, Sub, SubAssign};
+
+use num_rational::Ratio;
+
+/// Exact time or coordinate
+pub type Fraction = Ratio<i128>;
+
+/// A position or a direction
+#[derive(Debug, PartialEq, Eq, Hash, Clone, Copy)]
+pub struct Point {
+    pub x: i128,
+    pub y: i128,
+    pub z: i128,
+}
+
+impl Point {
+    pub fn new(x: i128, y: i128, z: i128) -> Self {
+        Self { x, y, z }
+    }
+
+    pub fn cross(&self, other: &Self) -> Self {
+        Self {
+            x: self.y * other.z - self.z * other.y,
+            y: self.z * other.x - self.x * other.z,
+            z: self.x * other.y - self.y * other.x,
+        }
+    }
+
+    pub fn dot(&self, other: &Self) -> i128 {
+        self.x * other.x + self.y * other.y + self.z * other.z
+    }
+
+    pub fn is_zero(&self) -> bool {
+        *self == Self::new(0, 0, 0)
+    }
+}
+
+impl Add<Self> for Point {
+    type Output = Self;
+
+    fn add(self, rhs: Self) -> Self::Output {
+        let x = self.x + rhs.x;
+        let y = self.y + rhs.y;
+        let z = self.z + rhs.z;
+        Self { x, y, z }
+    }
+}
+
+impl AddAssign<Self> for Point {
+    fn add_assign(&mut self, rhs: Self) {
+        self.x += rhs.x;
+        self.y += rhs.y;
+        self.z += rhs.z;
+    }
+}
+
+impl Sub<Self> for Point {
+    type Output = Self;
+
+    fn sub(self, rhs: Self) -> Self::Output {
+        let x = self.x - rhs.x;
+        let y = self.y - rhs.y;
+        let z = self.z - rhs.z;
+        Self { x, y, z }
+    }
+}
+
+impl SubAssign<Self> for Point {
+    fn sub_assign(&mut self, rhs: Self) {
+        self.x -= rhs.x;
+        self.y -= rhs.y;
+        self.z -= rhs.z;
+    }
+}
+
+impl Mul<i128> for Point {
+    type Output = Self;
+
+    fn mul(self, rhs: i128) -> Self::Output {
+        Self::new(self.x * rhs, self.y * rhs, self.z * rhs)
+    }
+}
+
+/// A plane through the origin
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub struct Plane {
+    pub normal: Point,
+}
+
+impl Plane {
+    pub const XY: Self = Self {
+        normal: Point { x: 0, y: 0, z: 1 },
+    };
+    pub const XZ: Self = Self {
+        normal: Point { x: 0, y: 1, z: 0 },
+    };
+    pub const YZ: Self = Self {
+        normal: Point { x: 1, y: 0, z: 0 },
+    };
+}
+
+/// How two rays relate to each other, times can be negative for points in the past
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum Meeting {
+    /// The lines never get any closer or further apart
+    Parallel,
+    /// Both rays move along the same line
+    Coincident,
+    /// The lines pass each other without touching, these are the times of the closest approach
+    Skew(Fraction, Fraction),
+    /// The lines cross, when each of the rays is at the crossing
+    Crossing(Fraction, Fraction),
+}
+
+/// Something that starts at the origin at time 0 and moves by direction every unit of time
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub struct Ray {
+    pub origin: Point,
+    pub direction: Point,
+}
+
+impl Ray {
+    pub fn new(origin: Point, direction: Point) -> Self {
+        Self { origin, direction }
+    }
+
+    /// x, y and z at the given time
+    pub fn position_at(&self, time: Fraction) -> [Fraction; 3] {
+        let Ray { origin, direction } = self;
+        [
+            (origin.x, direction.x),
+            (origin.y, direction.y),
+            (origin.z, direction.z),
+        ]
+        .map(|(o, d)| Fraction::from_integer(o) + time * d)
+    }
+
+    /// Projection along the normal onto the plane. To keep whole numbers, the coordinates are
+    /// scaled by the squared length of the normal, which is 1 for the planes along the axes.
+    /// Times don't change.
+    pub fn projected(&self, plane: Plane) -> Self {
+        let n = plane.normal;
+        let project = |p: Point| p * n.dot(&n) - n * p.dot(&n);
+        Self::new(project(self.origin), project(self.direction))
+    }
+
+    /// The times when both rays are closest to each other, None for parallel lines
+    pub fn closest_approach(&self, other: &Self) -> Option<(Fraction, Fraction)> {
+        let n = self.direction.cross(&other.direction);
+        if n.is_zero() {
+            return None;
+        }
+        let w = other.origin - self.origin;
+        let length = n.dot(&n);
+        Some((
+            Fraction::new(w.cross(&other.direction).dot(&n), length),
+            Fraction::new(w.cross(&self.direction).dot(&n), length),
+        ))
+    }
+
+    /// Distance between the lines at the closest approach, None for parallel lines
+    pub fn closest_distance(&self, other: &Self) -> Option<f64> {
+        let n = self.direction.cross(&other.direction);
+        if n.is_zero() {
+            return None;
+        }
+        let w = other.origin - self.origin;
+        Some(w.dot(&n).abs() as f64 / (n.dot(&n) as f64).sqrt())
+    }
+
+    pub fn meeting(&self, other: &Self) -> Meeting {
+        let w = other.origin - self.origin;
+        match self.closest_approach(other) {
+            None if w.cross(&self.direction).is_zero() => Meeting::Coincident,
+            None => Meeting::Parallel,
+            Some((t, u)) if w.dot(&self.direction.cross(&other.direction)) == 0 => {
+                Meeting::Crossing(t, u)
+            }
+            Some((t, u)) => Meeting::Skew(t, u),
+        }
+    }
+
+    /// How the projections onto the plane meet, the rays shouldn't move along the normal
+    pub fn meeting_in(&self, other: &Self, plane: Plane) -> Meeting {
+        // self + a t and other + b u meet when a t - b u = c, with everything crossed with the
+        // normal to drop the part along the normal
+        let n = plane.normal;
+        let a = self.direction.cross(&n);
+        let b = other.direction.cross(&n);
+        let c = (other.origin - self.origin).cross(&n);
+        let ab = a.cross(&b);
+        if ab.is_zero() {
+            if c.cross(&a).is_zero() {
+                Meeting::Coincident
+            } else {
+                Meeting::Parallel
+            }
+        } else {
+            let length = ab.dot(&ab);
+            Meeting::Crossing(
+                Fraction::new(c.cross(&b).dot(&ab), length),
+                Fraction::new(c.cross(&a).dot(&ab), length),
+            )
+        }
+    }
+
+    /// When both rays are in the same place at the same time
+    pub fn collision(&self, other: &Self) -> Option<Fraction> {
+        let w = other.origin - self.origin;
+        let v = self.direction - other.direction;
+        if v.is_zero() {
+            return w.is_zero().then(|| Fraction::from_integer(0));
+        }
+        w.cross(&v)
+            .is_zero()
+            .then(|| Fraction::new(w.dot(&v), v.dot(&v)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ray(origin: (i128, i128, i128), direction: (i128, i128, i128)) -> Ray {
+        Ray::new(
+            Point::new(origin.0, origin.1, origin.2),
+            Point::new(direction.0, direction.1, direction.2),
+        )
+    }
+
+    #[test]
+    fn meetings() {
+        let a = ray((0, 0, 0), (1, 0, 0));
+        let b = ray((2, -3, 0), (0, 1, 0));
+        assert_eq!(a.meeting(&b), Meeting::Crossing(2.into(), 3.into()));
+        let skew = ray((2, -3, 5), (0, 1, 0));
+        assert_eq!(a.meeting(&skew), Meeting::Skew(2.into(), 3.into()));
+        assert_eq!(a.closest_distance(&skew), Some(5.0));
+        assert_eq!(
+            a.meeting_in(&skew, Plane::XY),
+            Meeting::Crossing(2.into(), 3.into())
+        );
+        assert_eq!(a.meeting(&ray((0, 1, 0), (2, 0, 0))), Meeting::Parallel);
+        assert_eq!(a.meeting(&ray((-4, 0, 0), (-1, 0, 0))), Meeting::Coincident);
+        assert_eq!(
+            a.meeting_in(&ray((7, 0, 1), (3, 0, 0)), Plane::XY),
+            Meeting::Coincident
+        );
+    }
+
+    #[test]
+    fn collisions_and_projections() {
+        let a = ray((0, 0, 0), (1, 2, 3));
+        let b = ray((3, 1, 0), (0, 2, 4));
+        assert_eq!(a.collision(&b), None);
+        let c = ray((3, 0, 9), (0, 2, 0));
+        assert_eq!(a.collision(&c), Some(3.into()));
+        assert_eq!(
+            a.position_at(Fraction::new(1, 2)),
+            [Fraction::new(1, 2), 1.into(), Fraction::new(3, 2)]
+        );
+
+        assert_eq!(a.projected(Plane::XZ), ray((0, 0, 0), (1, 0, 3)));
+        // onto the plane x + y = 0, scaled by 2
+        let diagonal = Plane {
+            normal: Point::new(1, 1, 0),
+        };
+        assert_eq!(b.projected(diagonal), ray((2, -2, 0), (-2, 2, 8)));
+    }
+}
@@ -1,7 +1,12 @@
-use std::fmt::Debug;
-use std::ops::{Add, AddAssign, Sub, SubAssign};
+pub mod geometry;
 
+use std::error::Error;
+use std::fmt::{Debug, Display};
+
+use geometry::{Fraction, Meeting, Plane, Point, Ray};
 use itertools::Itertools;
+use num_rational::BigRational;
+use num_traits::{Signed, ToPrimitive, Zero};
 
 use nom::{
     bytes::complete::tag,
@@ -17,144 +22,187 @@ pub fn process_part1(input: &str) -> String {
     } else {
         (200000000000000, 400000000000000)
     };
-    let (input, hailstones) = parse_input(input).unwrap();
-    assert!(input.is_empty());
-    let range = min..=max;
+    let range = Fraction::from_integer(min)..=Fraction::from_integer(max);
 
-    let intersections = hailstones
-        .iter()
-        .combinations(2)
-        .filter_map(|v| {
-            let a = v[0];
-            let b = v[1];
-            a.intersect_xy(b)
-        })
-        .filter(|(x, y)| range.contains(x) && range.contains(y))
-        .collect::<Vec<_>>();
+    let intersections = path_crossings(input)
+        .into_iter()
+        .filter(|c| c.when() == When::Future)
+        .filter(|c| range.contains(&c.x) && range.contains(&c.y))
+        .count();
 
-    intersections.len().to_string()
+    intersections.to_string()
 }
 
 pub fn process_part2(input: &str) -> String {
-    let search_width = if input.len() == 119 {
-        10_i128
-    } else {
-        300_i128
-    };
     let (input, hailstones) = parse_input(input).unwrap();
     assert!(input.is_empty());
-    let sample_size = 4;
-    let velocity;
-    for x in -search_width..=search_width {
-        for y in -search_width..=search_width {
-            let dpoint = Point { x, y, z: 0 };
-            if hailstones
-                .iter()
-                .take(sample_size)
-                .map(|h| h - dpoint)
-                .combinations(2)
-                .filter_map(|v| {
-                    let a = &v[0];
-                    let b = &v[1];
-                    b.intersect_xy(a)
-                })
-                .all_equal()
-            {
-                // now try xz;
-
-                for z in -search_width..=search_width {
-                    let dpoint = Point { x, y, z };
-                    if hailstones
-                        .iter()
-                        .take(sample_size)
-                        .map(|h| h - dpoint)
-                        .combinations(2)
-                        .filter_map(|v| {
-                            let a = &v[0];
-                            let b = &v[1];
-                            b.intersect_xz(a)
-                        })
-                        .all_equal()
-                        && hailstones
-                            .iter()
-                            .take(sample_size)
-                            .map(|h| h - dpoint)
-                            .combinations(2)
-                            .filter_map(|v| {
-                                let a = &v[0];
-                                let b = &v[1];
-                                b.intersect_yz(a)
-                            })
-                            .all_equal()
-                    {
-                        velocity = Point { x, y, z };
-                        let a = &hailstones[0] - velocity;
-                        let b = &hailstones[1] - velocity;
-                        let (solx, soly) = a.intersect_xy(&b).expect("found solution");
-                        let (solx2, solz) = a.intersect_xz(&b).expect("found solution");
-                        assert_eq!(solx, solx2);
-                        return (solx + soly + solz).to_string();
-                    }
-                }
-            }
-        }
-    }
-    unreachable!("Problem has no solution");
+    let (position, _) =
+        throw_rock(&hailstones).expect("There is a throw that hits every hailstone");
+    (position.x + position.y + position.z).to_string()
 }
 
-#[derive(Debug, PartialEq, Eq, Hash, Clone, Copy)]
-struct Point {
-    x: i128,
-    y: i128,
-    z: i128,
+/// Where the paths of two hailstones cross, looking only at x and y
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PathCrossing {
+    /// Indices of the hailstones in the input
+    pub hailstones: (usize, usize),
+    /// When each of the hailstones is at the crossing, negative if that was in the past
+    pub times: (Fraction, Fraction),
+    pub x: Fraction,
+    pub y: Fraction,
 }
 
-impl Point {
-    fn parse(input: &str) -> IResult<&str, Self> {
-        let (input, x) = nomi128(input)?;
-        let (input, _) = preceded(tag(","), space1)(input)?;
-        let (input, y) = nomi128(input)?;
-        let (input, _) = preceded(tag(","), space1)(input)?;
-        let (input, z) = nomi128(input)?;
-        Ok((input, Self { x, y, z }))
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum When {
+    Future,
+    PastForFirst,
+    PastForSecond,
+    PastForBoth,
+}
+
+impl PathCrossing {
+    pub fn when(&self) -> When {
+        match (self.times.0.is_negative(), self.times.1.is_negative()) {
+            (false, false) => When::Future,
+            (true, false) => When::PastForFirst,
+            (false, true) => When::PastForSecond,
+            (true, true) => When::PastForBoth,
+        }
     }
 }
 
-impl Add<Self> for Point {
-    type Output = Self;
+/// Every pair of hailstones whose paths cross in the x-y plane, whether that's in the future or in
+/// the past
+pub fn path_crossings(input: &str) -> Vec<PathCrossing> {
+    let (input, hailstones) = parse_input(input).unwrap();
+    assert!(input.is_empty());
+    hailstones
+        .iter()
+        .map(HailStone::ray)
+        .enumerate()
+        .tuple_combinations()
+        .filter_map(|((i, a), (j, b))| match a.meeting_in(&b, Plane::XY) {
+            Meeting::Crossing(t, u) => {
+                let [x, y, _] = a.position_at(t);
+                Some(PathCrossing {
+                    hailstones: (i, j),
+                    times: (t, u),
+                    x,
+                    y,
+                })
+            }
+            _ => None,
+        })
+        .collect()
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum ThrowError {
+    /// The hailstones don't pin down a single throw, e.g. there are fewer than 3 of them
+    Degenerate,
+    /// No straight throw can hit all hailstones
+    Inconsistent,
+    /// The only throw that hits all hailstones doesn't start at whole number coordinates
+    NotIntegral,
+    /// The throw has a coordinate that doesn't fit in an i128
+    Overflow,
+}
 
-    fn add(self, rhs: Self) -> Self::Output {
-        let x = self.x + rhs.x;
-        let y = self.y + rhs.y;
-        let z = self.z + rhs.z;
-        Self { x, y, z }
+impl Display for ThrowError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ThrowError::Degenerate => f.write_str("The hailstones allow more than one throw"),
+            ThrowError::Inconsistent => f.write_str("No throw hits all hailstones"),
+            ThrowError::NotIntegral => f.write_str("The throw doesn't start at whole coordinates"),
+            ThrowError::Overflow => f.write_str("The throw is too far out to fit in an i128"),
+        }
     }
 }
 
-impl AddAssign<Self> for Point {
-    fn add_assign(&mut self, rhs: Self) {
-        self.x += rhs.x;
-        self.y += rhs.y;
-        self.z += rhs.z;
+impl Error for ThrowError {}
+
+/// Position and velocity of the rock that hits every hailstone.
+///
+/// The rock hits hailstone i when (P - p_i) x (V - v_i) = 0. Subtracting that for two hailstones
+/// gets rid of the P x V term, leaving P x (v_j - v_i) + (p_j - p_i) x V = p_j x v_j - p_i x v_i,
+/// which is linear in P and V. Every next pair of hailstones adds three of those equations, which
+/// are solved exactly with rational numbers.
+fn throw_rock(hailstones: &[HailStone]) -> Result<(Point, Point), ThrowError> {
+    let mut rows: Vec<Vec<BigRational>> = hailstones
+        .windows(2)
+        .flat_map(|pair| {
+            let (i, j) = (&pair[0], &pair[1]);
+            let w = j.velocity - i.velocity;
+            let d = j.position - i.position;
+            let rhs = j.position.cross(&j.velocity) - i.position.cross(&i.velocity);
+            [
+                [0, w.z, -w.y, 0, -d.z, d.y, rhs.x],
+                [-w.z, 0, w.x, d.z, 0, -d.x, rhs.y],
+                [w.y, -w.x, 0, -d.y, d.x, 0, rhs.z],
+            ]
+        })
+        .map(|row| {
+            row.iter()
+                .map(|&n| BigRational::from_integer(n.into()))
+                .collect()
+        })
+        .collect();
+
+    // Gauss-Jordan elimination
+    let unknowns = 6;
+    for column in 0..unknowns {
+        let Some(pivot) = (column..rows.len()).find(|&r| !rows[r][column].is_zero()) else {
+            return Err(ThrowError::Degenerate);
+        };
+        rows.swap(column, pivot);
+        let pivot_row = rows[column].clone();
+        for (r, row) in rows.iter_mut().enumerate() {
+            if r == column || row[column].is_zero() {
+                continue;
+            }
+            let factor = &row[column] / &pivot_row[column];
+            row.iter_mut()
+                .zip(pivot_row.iter())
+                .for_each(|(value, pivot_value)| *value -= &factor * pivot_value);
+        }
+    }
+    // all equations beyond the first six are combinations of those, or they contradict them
+    if rows[unknowns..].iter().any(|row| !row[unknowns].is_zero()) {
+        return Err(ThrowError::Inconsistent);
     }
-}
 
-impl Sub<Self> for Point {
-    type Output = Self;
+    let solution = (0..unknowns)
+        .map(|r| {
+            let value = &rows[r][unknowns] / &rows[r][r];
+            if value.is_integer() {
+                value.to_integer().to_i128().ok_or(ThrowError::Overflow)
+            } else {
+                Err(ThrowError::NotIntegral)
+            }
+        })
+        .collect::<Result<Vec<i128>, _>>()?;
+    let position = Point::new(solution[0], solution[1], solution[2]);
+    let velocity = Point::new(solution[3], solution[4], solution[5]);
 
-    fn sub(self, rhs: Self) -> Self::Output {
-        let x = self.x - rhs.x;
-        let y = self.y - rhs.y;
-        let z = self.z - rhs.z;
-        Self { x, y, z }
+    // the pairwise differences only hold if the rock hits one of the hailstones as well
+    let hits_all = hailstones
+        .iter()
+        .all(|h| (position - h.position).cross(&(velocity - h.velocity)) == Point::new(0, 0, 0));
+    if hits_all {
+        Ok((position, velocity))
+    } else {
+        Err(ThrowError::Inconsistent)
     }
 }
 
-impl SubAssign<Self> for Point {
-    fn sub_assign(&mut self, rhs: Self) {
-        self.x -= rhs.x;
-        self.y -= rhs.y;
-        self.z -= rhs.z;
+impl Point {
+    fn parse(input: &str) -> IResult<&str, Self> {
+        let (input, x) = nomi128(input)?;
+        let (input, _) = preceded(tag(","), space1)(input)?;
+        let (input, y) = nomi128(input)?;
+        let (input, _) = preceded(tag(","), space1)(input)?;
+        let (input, z) = nomi128(input)?;
+        Ok((input, Self { x, y, z }))
     }
 }
 
@@ -173,91 +221,8 @@ impl HailStone {
         Ok((input, Self { position, velocity }))
     }
 
-    fn intersect_xy(&self, other: &Self) -> Option<(i128, i128)> {
-        let eq_self = LinearEquation::from_point(
-            self.position.x,
-            self.position.y,
-            self.velocity.x,
-            self.velocity.y,
-        );
-        let eq_other = LinearEquation::from_point(
-            other.position.x,
-            other.position.y,
-            other.velocity.x,
-            other.velocity.y,
-        );
-        match eq_self.intersect(&eq_other) {
-            Some((x, y)) => {
-                if (x - self.position.x).signum() == self.velocity.x.signum()
-                    && (y - self.position.y).signum() == self.velocity.y.signum()
-                    && (x - other.position.x).signum() == other.velocity.x.signum()
-                    && (y - other.position.y).signum() == other.velocity.y.signum()
-                {
-                    Some((x, y))
-                } else {
-                    None
-                }
-            }
-            None => None,
-        }
-    }
-
-    fn intersect_xz(&self, other: &Self) -> Option<(i128, i128)> {
-        let eq_self = LinearEquation::from_point(
-            self.position.x,
-            self.position.z,
-            self.velocity.x,
-            self.velocity.z,
-        );
-        let eq_other = LinearEquation::from_point(
-            other.position.x,
-            other.position.z,
-            other.velocity.x,
-            other.velocity.z,
-        );
-        match eq_self.intersect(&eq_other) {
-            Some((x, z)) => {
-                if (x - self.position.x).signum() == self.velocity.x.signum()
-                    && (z - self.position.z).signum() == self.velocity.z.signum()
-                    && (x - other.position.x).signum() == other.velocity.x.signum()
-                    && (z - other.position.z).signum() == other.velocity.z.signum()
-                {
-                    Some((x, z))
-                } else {
-                    None
-                }
-            }
-            None => None,
-        }
-    }
-
-    fn intersect_yz(&self, other: &Self) -> Option<(i128, i128)> {
-        let eq_self = LinearEquation::from_point(
-            self.position.y,
-            self.position.z,
-            self.velocity.y,
-            self.velocity.z,
-        );
-        let eq_other = LinearEquation::from_point(
-            other.position.y,
-            other.position.z,
-            other.velocity.y,
-            other.velocity.z,
-        );
-        match eq_self.intersect(&eq_other) {
-            Some((y, z)) => {
-                if (y - self.position.y).signum() == self.velocity.y.signum()
-                    && (z - self.position.z).signum() == self.velocity.z.signum()
-                    && (y - other.position.y).signum() == other.velocity.y.signum()
-                    && (z - other.position.z).signum() == other.velocity.z.signum()
-                {
-                    Some((y, z))
-                } else {
-                    None
-                }
-            }
-            None => None,
-        }
+    fn ray(&self) -> Ray {
+        Ray::new(self.position, self.velocity)
     }
 }
 
@@ -275,64 +240,6 @@ impl Debug for HailStone {
     }
 }
 
-impl Add<Point> for HailStone {
-    type Output = Self;
-
-    fn add(self, rhs: Point) -> Self::Output {
-        let position = self.position;
-        let velocity = self.velocity + rhs;
-        Self { position, velocity }
-    }
-}
-
-impl AddAssign<Point> for HailStone {
-    fn add_assign(&mut self, rhs: Point) {
-        self.velocity += rhs;
-    }
-}
-
-impl Sub<Point> for &HailStone {
-    type Output = HailStone;
-
-    fn sub(self, rhs: Point) -> Self::Output {
-        let position = self.position;
-        let velocity = self.velocity - rhs;
-        HailStone { position, velocity }
-    }
-}
-
-impl SubAssign<Point> for HailStone {
-    fn sub_assign(&mut self, rhs: Point) {
-        self.velocity -= rhs;
-    }
-}
-
-/// Equation of the form ax + by = c
-struct LinearEquation {
-    a: i128,
-    b: i128,
-    c: i128,
-}
-
-impl LinearEquation {
-    fn from_point(x: i128, y: i128, dx: i128, dy: i128) -> Self {
-        let a = dy;
-        let b = -dx;
-        let c = dy * x - dx * y;
-        Self { a, b, c }
-    }
-
-    fn intersect(&self, other: &Self) -> Option<(i128, i128)> {
-        let d = other.b * self.a - other.a * self.b;
-        if d == 0 {
-            return None;
-        }
-        let x = (other.b * self.c - self.b * other.c) / d;
-        let y = (other.c * self.a - self.c * other.a) / d;
-        Some((x, y))
-    }
-}
-
 fn parse_input(input: &str) -> IResult<&str, Vec<HailStone>> {
     let (input, lines) = separated_list1(newline, HailStone::parse)(input)?;
     Ok((input, lines))
@@ -363,4 +270,77 @@ mod tests {
         let result = process_part2(input);
         assert_eq!(result, "47");
     }
+
+    #[test]
+    fn crossings() {
+        let input = "19, 13, 30 @ -2,  1, -2
+18, 19, 22 @ -1, -1, -2
+20, 25, 34 @ -2, -2, -4
+12, 31, 28 @ -1, -2, -1
+20, 19, 15 @  1, -5, -3";
+        let crossings = path_crossings(input);
+        // only B and C have parallel paths
+        assert_eq!(crossings.len(), 9);
+        assert_eq!(
+            crossings[0],
+            PathCrossing {
+                hailstones: (0, 1),
+                times: (Fraction::new(7, 3), Fraction::new(11, 3)),
+                x: Fraction::new(43, 3),
+                y: Fraction::new(46, 3),
+            }
+        );
+        let when: Vec<_> = crossings.iter().map(|c| c.when()).collect();
+        use When::*;
+        assert_eq!(
+            when,
+            vec![
+                Future,
+                Future,
+                Future,
+                PastForFirst,
+                Future,
+                PastForBoth,
+                Future,
+                PastForSecond,
+                PastForBoth
+            ]
+        );
+    }
+
+    #[test]
+    fn throw_errors() {
+        let (_, hailstones) = parse_input(
+            "19, 13, 30 @ -2,  1, -2
+18, 19, 22 @ -1, -1, -2
+20, 25, 34 @ -2, -2, -4
+12, 31, 28 @ -1, -2, -1
+20, 19, 15 @  1, -5, -3",
+        )
+        .unwrap();
+        let (position, velocity) = throw_rock(&hailstones).unwrap();
+        assert_eq!(position, Point::new(24, 13, 10));
+        assert_eq!(velocity, Point::new(-3, 1, 2));
+
+        assert_eq!(throw_rock(&hailstones[..2]), Err(ThrowError::Degenerate));
+
+        let mut missed = hailstones.clone();
+        missed.push(HailStone::parse("1, 2, 3 @ 4, 5, 6").unwrap().1);
+        assert_eq!(throw_rock(&missed), Err(ThrowError::Inconsistent));
+
+        // the rock starts at (0, 0, 0) with velocity (1/2, 0, 0)
+        let (_, halves) =
+            parse_input("1, -2, 0 @ 0, 1, 0\n2, 0, -4 @ 0, 0, 1\n-3, 0, 0 @ 1, 0, 0").unwrap();
+        assert_eq!(throw_rock(&halves), Err(ThrowError::NotIntegral));
+
+        // the rock starts at (2^127, 0, 0), just beyond i128::MAX
+        let (_, far) = parse_input(
+            "168811955464684315858783496655603761152, -1, 0 @ 0, 1, 0
+167482727468899399985879689595323416576, 0, -2 @ 0, 0, 1
+166153499473114484112975882535043072000, -3, -3 @ 0, 1, 1
+164824271477329568240072075474762727420, 0, -4 @ 1, 0, 1",
+        )
+        .unwrap();
+        assert_eq!(throw_rock(&far), Err(ThrowError::Overflow));
+    }
 }
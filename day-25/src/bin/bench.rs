@@ -0,0 +1,52 @@
+use answer::{parse_graph, Backend};
+use std::{fs, time::Instant};
+
+const RUNS: u32 = 3;
+
+fn main() {
+    let file = fs::read_to_string("./input.txt").unwrap();
+    let graph = parse_graph(&file);
+    let mut cuts = vec![];
+    for backend in [Backend::StoerWagner, Backend::MaxFlow] {
+        let start = Instant::now();
+        let mut cut = None;
+        for _ in 0..RUNS {
+            cut = Some(graph.minimum_cut(backend).unwrap());
+        }
+        let time = start.elapsed() / RUNS;
+        let cut = cut.unwrap();
+        println!(
+            "{backend:?}: weight {}, partitions of {} and {} nodes - {time:?}",
+            cut.weight,
+            cut.partitions[0].len(),
+            cut.partitions[1].len()
+        );
+        assert!(
+            graph.is_disconnected_by(&cut.edges),
+            "{backend:?} should cut the graph in two"
+        );
+        cuts.push(cut);
+    }
+    // the real input has a single minimum cut, so both backends have to find that one
+    assert_eq!(
+        cuts[0].weight, cuts[1].weight,
+        "Both backends should find a cut of the same weight"
+    );
+    assert_eq!(
+        cuts[0].partitions.each_ref().map(Vec::len),
+        cuts[1].partitions.each_ref().map(Vec::len),
+        "Both backends should split the graph into the same sizes"
+    );
+    let edges: Vec<_> = cuts
+        .into_iter()
+        .map(|cut| {
+            let mut edges = cut.edges;
+            edges.sort();
+            edges
+        })
+        .collect();
+    assert_eq!(
+        edges[0], edges[1],
+        "Both backends should cut the same edges"
+    );
+}
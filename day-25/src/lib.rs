@@ -1,29 +1,84 @@
-use std::collections::{HashMap, HashSet};
+use std::{
+    collections::{HashMap, VecDeque},
+    error::Error,
+    fmt::Display,
+};
 
 use nom::{
     bytes::complete::tag,
-    character::complete::{alpha1, newline, space1},
+    character::complete::{alpha1, char as nomchar, newline, space1, u32 as nomu32},
+    combinator::opt,
     multi::separated_list1,
-    sequence::terminated,
+    sequence::{pair, preceded, terminated},
     IResult,
 };
 
 pub fn process_part1(input: &str) -> String {
-    let (input, named_nodes) = parse_input(input).unwrap();
-    debug_assert!(input.is_empty());
-    // named_nodes.iter().for_each(|n| n.mermaid());
-    let mut graph: Graph = named_nodes.into();
-    let solution = graph.solve();
-    solution.to_string()
+    let graph = parse_graph(input);
+    let cut = graph
+        .minimum_cut_if_weight(3, Backend::MaxFlow)
+        .expect("Three wires can be cut to split the machine in two");
+    (cut.partitions[0].len() * cut.partitions[1].len()).to_string()
 }
 
 pub fn process_part2(_input: &str) -> String {
     "Merry Christmas!".to_string()
 }
 
+/// Parses the wiring diagram, an optional `*weight` after a connection gives it that weight
+pub fn parse_graph(input: &str) -> Graph {
+    let (input, named_nodes) = parse_input(input).unwrap();
+    assert!(input.trim().is_empty());
+    // named_nodes.iter().for_each(|n| n.mermaid());
+    named_nodes.into()
+}
+
+/// The lightest set of edges that splits the graph in two. Only two-way cuts are supported,
+/// splitting into more parts would need a different algorithm than Stoer-Wagner or max-flow.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MinimumCut {
+    pub weight: usize,
+    /// (node in the first partition, node in the second partition, weight) for every edge that is cut
+    pub edges: Vec<(String, String, usize)>,
+    /// Node names of both halves, sorted, the first one contains the first node of the input
+    pub partitions: [Vec<String>; 2],
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Backend {
+    /// Contracts the graph one node at a time, O(V^3) with the dense scan in every phase
+    StoerWagner,
+    /// Edmonds-Karp maximum flow from the first node to every other node, which is fast when the
+    /// minimum cut is light
+    MaxFlow,
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum CutError {
+    /// A graph with fewer than 2 nodes can't be cut in two
+    TooFewNodes,
+    /// The minimum cut doesn't weigh what was asked for
+    WrongWeight { required: usize, minimum: usize },
+}
+
+impl Display for CutError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            CutError::TooFewNodes => f.write_str("The graph needs at least 2 nodes to be cut"),
+            CutError::WrongWeight { required, minimum } => write!(
+                f,
+                "The minimum cut has weight {}, not {}",
+                minimum, required
+            ),
+        }
+    }
+}
+
+impl Error for CutError {}
+
 struct NamedNode<'a> {
     name: &'a str,
-    connections: Vec<&'a str>,
+    connections: Vec<(&'a str, usize)>,
 }
 
 #[derive(Debug, Clone)]
@@ -33,52 +88,152 @@ struct Edge {
 }
 
 impl<'a> NamedNode<'a> {
-    fn parse(input: &'a str) -> IResult<&str, Self> {
+    fn parse(input: &'a str) -> IResult<&'a str, Self> {
         let (input, name) = terminated(alpha1, tag(": "))(input)?;
-        let (input, connections) = separated_list1(space1, alpha1)(input)?;
+        let (input, connections) =
+            separated_list1(space1, pair(alpha1, opt(preceded(nomchar('*'), nomu32))))(input)?;
+        let connections = connections
+            .into_iter()
+            .map(|(c, weight)| (c, weight.unwrap_or(1) as usize))
+            .collect();
         Ok((input, Self { name, connections }))
     }
 
     #[allow(dead_code)]
     fn mermaid(&self) {
-        self.connections.iter().for_each(|&c| {
+        self.connections.iter().for_each(|&(c, _)| {
             println!("{} --- {}", self.name, c);
         });
     }
 }
 
 #[derive(Debug, Clone)]
-struct Graph {
+pub struct Graph {
+    names: Vec<String>,
+    /// (a, b, weight) for every edge in the input
+    edges: Vec<(usize, usize, usize)>,
     nodes: Vec<Vec<Edge>>,
+    /// Original nodes that were contracted into every node
+    members: Vec<Vec<usize>>,
+    /// Whether the node hasn't been contracted into another one
+    active: Vec<bool>,
     node_count: usize,
 }
 
 impl Graph {
-    fn solve(&mut self) -> usize {
-        let mut cuts = Vec::new();
-        while self.node_count > 2 {
-            let cut = self.sw_phase();
-            self.contract_nodes(&cut);
-            cuts.push(cut);
+    pub fn minimum_cut(&self, backend: Backend) -> Result<MinimumCut, CutError> {
+        if self.node_count < 2 {
+            return Err(CutError::TooFewNodes);
         }
-        let minimum_cut = cuts
+        let (weight, side) = match backend {
+            Backend::StoerWagner => self.stoer_wagner(),
+            Backend::MaxFlow => FlowNetwork::new(self).minimum_cut(),
+        };
+        Ok(self.describe_cut(weight, &side))
+    }
+
+    /// Whether taking out the edges leaves the graph in more than one piece. Edges between nodes
+    /// that aren't in the graph don't cut anything, so they make this false.
+    pub fn is_disconnected_by(&self, edges: &[(String, String, usize)]) -> bool {
+        let index: HashMap<&str, usize> = self
+            .names
             .iter()
-            .min_by(|a, b| a.weight.cmp(&b.weight))
-            .expect("There is a minimum cut")
-            .clone();
-
-        // let's see if we get lucky and can do this without DFS
-        let mut dfs_graph = DFSGraph::new();
-        cuts.iter()
-            .take_while(|c| **c != minimum_cut)
-            .for_each(|c| dfs_graph.add_edge(c.a, c.b));
-        let component_size = if dfs_graph.nodes.contains_key(&minimum_cut.a) {
-            dfs_graph.dfs(minimum_cut.a)
+            .enumerate()
+            .map(|(i, name)| (name.as_str(), i))
+            .collect();
+        let removed: Option<Vec<(usize, usize)>> = edges
+            .iter()
+            .map(|(a, b, _)| Some((*index.get(a.as_str())?, *index.get(b.as_str())?)))
+            .collect();
+        let Some(removed) = removed else {
+            return false;
+        };
+        let mut neighbours = vec![vec![]; self.names.len()];
+        for &(a, b, _) in self.edges.iter() {
+            if !removed.contains(&(a, b)) && !removed.contains(&(b, a)) {
+                neighbours[a].push(b);
+                neighbours[b].push(a);
+            }
+        }
+        let mut reached = vec![false; self.names.len()];
+        let mut queue = VecDeque::from([0]);
+        reached[0] = true;
+        while let Some(node) = queue.pop_front() {
+            for &next in neighbours[node].iter() {
+                if !reached[next] {
+                    reached[next] = true;
+                    queue.push_back(next);
+                }
+            }
+        }
+        reached.contains(&false)
+    }
+
+    /// The minimum cut if it weighs exactly `weight`. Only the minimum cut is checked, heavier
+    /// cuts of that weight aren't searched for.
+    pub fn minimum_cut_if_weight(
+        &self,
+        weight: usize,
+        backend: Backend,
+    ) -> Result<MinimumCut, CutError> {
+        let cut = self.minimum_cut(backend)?;
+        if cut.weight == weight {
+            Ok(cut)
         } else {
-            assert!(dfs_graph.nodes.contains_key(&minimum_cut.b));
-            dfs_graph.dfs(minimum_cut.b)
+            Err(CutError::WrongWeight {
+                required: weight,
+                minimum: cut.weight,
+            })
+        }
+    }
+
+    /// The lightest cut of all phases is the minimum cut, returns its weight and the nodes on one side
+    fn stoer_wagner(&self) -> (usize, Vec<usize>) {
+        let mut graph = self.clone();
+        let mut minimum: Option<(usize, Vec<usize>)> = None;
+        while graph.node_count > 1 {
+            let cut = graph.sw_phase();
+            // the cut of the phase separates the last node from all others
+            if minimum
+                .as_ref()
+                .is_none_or(|(weight, _)| cut.weight < *weight)
+            {
+                minimum = Some((cut.weight, graph.members[cut.b].clone()));
+            }
+            graph.contract_nodes(&cut);
+        }
+        minimum.expect("There is at least one phase")
+    }
+
+    fn describe_cut(&self, weight: usize, side: &[usize]) -> MinimumCut {
+        let mut in_first = vec![true; self.names.len()];
+        side.iter().for_each(|&n| in_first[n] = false);
+        if !in_first[0] {
+            in_first.iter_mut().for_each(|b| *b = !*b);
+        }
+        let edges: Vec<_> = self
+            .edges
+            .iter()
+            .filter(|&&(a, b, _)| in_first[a] != in_first[b])
+            .map(|&(a, b, weight)| {
+                let (a, b) = if in_first[a] { (a, b) } else { (b, a) };
+                (self.names[a].clone(), self.names[b].clone(), weight)
+            })
+            .collect();
+        debug_assert_eq!(edges.iter().map(|e| e.2).sum::<usize>(), weight);
+        let partition = |first: bool| {
+            let mut names: Vec<String> = (0..self.names.len())
+                .filter(|&n| in_first[n] == first)
+                .map(|n| self.names[n].clone())
+                .collect();
+            names.sort();
+            names
         };
-        (self.nodes.len() - component_size) * component_size
+        MinimumCut {
+            weight,
+            edges,
+            partitions: [partition(true), partition(false)],
+        }
     }
 
     /// Runs one phase of Stoer-Wagner algorithm and returns the cut to be made
@@ -89,22 +244,11 @@ impl Graph {
         let mut cut_set = vec![false; len];
         let mut weights: Vec<usize> = vec![0; len];
         for _ in 0..self.node_count {
-            let next_node = if let Some((i, _)) = weights
-                .iter()
-                .enumerate()
-                .filter(|&(i, w)| w > &0 && !cut_set[i])
-                .max_by(|(_, a), (_, b)| a.cmp(b))
-            {
-                i
-            } else {
-                // first time around we pick the first node that is still in the graph
-                self.nodes
-                    .iter()
-                    .enumerate()
-                    .find(|(_, v)| !v.is_empty())
-                    .expect("There is at least one node")
-                    .0
-            };
+            // the first time around all weights are 0, so any node that is still in the graph will do
+            let next_node = (0..len)
+                .filter(|&i| self.active[i] && !cut_set[i])
+                .max_by_key(|&i| weights[i])
+                .expect("There is at least one node");
             cut_set[next_node] = true;
             self.nodes[next_node]
                 .iter()
@@ -125,8 +269,8 @@ impl Graph {
         debug_assert!(cut.a < self.nodes.len());
         debug_assert!(cut.b < self.nodes.len());
         debug_assert!(cut.a != cut.b);
-        debug_assert!(!self.nodes[cut.a].is_empty());
-        debug_assert!(!self.nodes[cut.b].is_empty());
+        debug_assert!(self.active[cut.a]);
+        debug_assert!(self.active[cut.b]);
         // merge into the lowest id node
         let (source, destination) = (cut.a.max(cut.b), cut.a.min(cut.b));
 
@@ -167,6 +311,9 @@ impl Graph {
         });
 
         self.nodes[destination] = new_edges;
+        let members = std::mem::take(&mut self.members[source]);
+        self.members[destination].extend(members);
+        self.active[source] = false;
         self.node_count -= 1;
     }
 
@@ -183,78 +330,148 @@ impl Graph {
 }
 
 impl<'a> From<Vec<NamedNode<'a>>> for Graph {
-    fn from(value: Vec<NamedNode>) -> Self {
-        let node_names: Vec<&str> = value
+    fn from(value: Vec<NamedNode<'a>>) -> Self {
+        // nodes are numbered in the order they first appear
+        let mut indices: HashMap<&str, usize> = HashMap::new();
+        let mut names = Vec::new();
+        let mut index_of = |name: &'a str| {
+            *indices.entry(name).or_insert_with(|| {
+                names.push(name.to_string());
+                names.len() - 1
+            })
+        };
+        let edges: Vec<(usize, usize, usize)> = value
             .iter()
-            .flat_map(|n| n.connections.iter().cloned().chain(std::iter::once(n.name)))
-            .collect::<HashSet<&str>>()
-            .into_iter()
+            .flat_map(|n| {
+                n.connections
+                    .iter()
+                    .map(move |&(c, weight)| (n.name, c, weight))
+            })
+            .map(|(a, b, weight)| (index_of(a), index_of(b), weight))
             .collect();
-        let node_count = node_names.len();
+        let node_count = names.len();
         let mut nodes = vec![vec![]; node_count];
-        value.iter().for_each(|n| {
-            let idx = node_names
-                .iter()
-                .position(|&e| e == n.name)
-                .expect("Node exists");
-            n.connections.iter().for_each(|&c| {
-                let node = node_names
-                    .iter()
-                    .position(|&e| e == c)
-                    .expect("Node exists");
-                nodes[idx].push(Edge { node, weight: 1 });
-                nodes[node].push(Edge {
-                    node: idx,
-                    weight: 1,
-                })
-            });
+        edges.iter().for_each(|&(a, b, weight)| {
+            nodes[a].push(Edge { node: b, weight });
+            nodes[b].push(Edge { node: a, weight });
         });
-        Self { nodes, node_count }
+        Self {
+            names,
+            edges,
+            nodes,
+            members: (0..node_count).map(|n| vec![n]).collect(),
+            active: vec![true; node_count],
+            node_count,
+        }
     }
 }
 
-#[derive(Debug, Clone, PartialEq, Eq)]
-struct Cut {
-    a: usize,
-    b: usize,
-    weight: usize,
-}
-
-struct DFSGraph {
-    nodes: HashMap<usize, Vec<usize>>,
+/// Residual network for Edmonds-Karp, every undirected edge is a pair of arcs with ids `i` and `i ^ 1`
+struct FlowNetwork {
+    arcs: Vec<Vec<usize>>,
+    heads: Vec<usize>,
+    capacities: Vec<usize>,
 }
 
-impl DFSGraph {
-    fn new() -> Self {
+impl FlowNetwork {
+    fn new(graph: &Graph) -> Self {
+        let mut arcs = vec![vec![]; graph.names.len()];
+        let mut heads = Vec::with_capacity(graph.edges.len() * 2);
+        let mut capacities = Vec::with_capacity(graph.edges.len() * 2);
+        graph.edges.iter().for_each(|&(a, b, weight)| {
+            arcs[a].push(heads.len());
+            heads.push(b);
+            arcs[b].push(heads.len());
+            heads.push(a);
+            capacities.extend([weight, weight]);
+        });
         Self {
-            nodes: Default::default(),
+            arcs,
+            heads,
+            capacities,
         }
     }
-    fn add_edge(&mut self, a: usize, b: usize) {
-        self.nodes.entry(a).or_default().push(b);
-        self.nodes.entry(b).or_default().push(a);
+
+    /// Node 0 is on one side of the minimum cut and some other node is on the other side, so the
+    /// lightest minimum s-t cut from node 0 to any other node is the minimum cut
+    fn minimum_cut(&self) -> (usize, Vec<usize>) {
+        let mut minimum: Option<(usize, Vec<usize>)> = None;
+        for sink in 1..self.arcs.len() {
+            let limit = minimum.as_ref().map_or(usize::MAX, |(weight, _)| *weight);
+            if let Some(cut) = self.minimum_st_cut(0, sink, limit) {
+                minimum = Some(cut);
+            }
+        }
+        minimum.expect("There are at least 2 nodes")
     }
 
-    /// returns the size of the component connected to start
-    fn dfs(&mut self, start: usize) -> usize {
-        let mut visited = HashSet::new();
-        self.dfs_rec(start, &mut visited);
+    /// Weight of the cut and the nodes on the source side, or None if the cut isn't lighter than
+    /// `limit`
+    fn minimum_st_cut(
+        &self,
+        source: usize,
+        sink: usize,
+        limit: usize,
+    ) -> Option<(usize, Vec<usize>)> {
+        let mut residual = self.capacities.clone();
+        let mut flow = 0;
+        loop {
+            // breadth first search for the shortest augmenting path
+            let mut previous_arc: Vec<Option<usize>> = vec![None; self.arcs.len()];
+            let mut visited = vec![false; self.arcs.len()];
+            let mut queue = VecDeque::from([source]);
+            visited[source] = true;
+            while let Some(node) = queue.pop_front() {
+                if node == sink {
+                    break;
+                }
+                for &arc in self.arcs[node].iter() {
+                    let next = self.heads[arc];
+                    if residual[arc] > 0 && !visited[next] {
+                        visited[next] = true;
+                        previous_arc[next] = Some(arc);
+                        queue.push_back(next);
+                    }
+                }
+            }
 
-        visited.len()
-    }
+            if !visited[sink] {
+                // whatever can still be reached from the source is on its side of the cut
+                let side = (0..self.arcs.len()).filter(|&n| visited[n]).collect();
+                return Some((flow, side));
+            }
 
-    fn dfs_rec(&self, node: usize, visited: &mut HashSet<usize>) {
-        if visited.contains(&node) {
-            return;
-        }
-        visited.insert(node);
-        if let Some(neighbours) = self.nodes.get(&node) {
-            neighbours.iter().for_each(|&n| self.dfs_rec(n, visited));
+            let mut path = vec![];
+            let mut node = sink;
+            while let Some(arc) = previous_arc[node] {
+                path.push(arc);
+                node = self.heads[arc ^ 1];
+            }
+            let bottleneck = path
+                .iter()
+                .map(|&arc| residual[arc])
+                .min()
+                .expect("The sink is not the source");
+            path.iter().for_each(|&arc| {
+                residual[arc] -= bottleneck;
+                residual[arc ^ 1] += bottleneck;
+            });
+            flow += bottleneck;
+            if flow >= limit {
+                return None;
+            }
         }
     }
 }
 
-fn parse_input(input: &str) -> IResult<&str, Vec<NamedNode>> {
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Cut {
+    a: usize,
+    b: usize,
+    weight: usize,
+}
+
+fn parse_input(input: &str) -> IResult<&str, Vec<NamedNode<'_>>> {
     let (input, lines) = separated_list1(newline, NamedNode::parse)(input)?;
     Ok((input, lines))
 }
@@ -263,9 +480,7 @@ fn parse_input(input: &str) -> IResult<&str, Vec<NamedNode>> {
 mod tests {
     use super::*;
 
-    #[test]
-    fn part1() {
-        let input = "jqt: rhn xhk nvd
+    const EXAMPLE: &str = "jqt: rhn xhk nvd
 rsh: frs pzl lsr
 xhk: hfx
 cmg: qnr nvd lhk bvb
@@ -278,7 +493,105 @@ nvd: lhk
 lsr: lhk
 rzs: qnr cmg lsr rsh
 frs: qnr lhk lsr";
-        let result = process_part1(input);
+
+    #[test]
+    fn part1() {
+        let result = process_part1(EXAMPLE);
         assert_eq!(result, "54");
     }
+
+    #[test]
+    fn minimum_cut() {
+        let cut = parse_graph(EXAMPLE)
+            .minimum_cut(Backend::StoerWagner)
+            .unwrap();
+        assert_eq!(cut.weight, 3);
+        let mut edges = cut.edges.clone();
+        edges.sort();
+        let edge = |a: &str, b: &str| (a.to_string(), b.to_string(), 1);
+        assert_eq!(
+            edges,
+            vec![edge("bvb", "cmg"), edge("hfx", "pzl"), edge("jqt", "nvd")]
+        );
+        assert_eq!(
+            cut.partitions[0],
+            vec!["bvb", "hfx", "jqt", "ntq", "rhn", "xhk"]
+        );
+        assert_eq!(cut.partitions[1].len(), 9);
+    }
+
+    #[test]
+    fn weighted_cut() {
+        // a heavy triangle and a light triangle, joined by two edges
+        let graph = parse_graph("a: b*5 c*5\nb: c*5 d*2\nc: e\nd: e*2 f*2\ne: f*2");
+        let cut = graph.minimum_cut(Backend::StoerWagner).unwrap();
+        assert_eq!(cut.weight, 3);
+        assert_eq!(cut.partitions, [vec!["a", "b", "c"], vec!["d", "e", "f"]]);
+        assert_eq!(
+            graph.minimum_cut_if_weight(2, Backend::StoerWagner),
+            Err(CutError::WrongWeight {
+                required: 2,
+                minimum: 3
+            })
+        );
+        assert_eq!(
+            graph
+                .minimum_cut_if_weight(2, Backend::StoerWagner)
+                .unwrap_err()
+                .to_string(),
+            "The minimum cut has weight 3, not 2"
+        );
+        // cutting off a is a cut of weight 10, but it isn't the minimum
+        assert_eq!(
+            graph.minimum_cut_if_weight(10, Backend::StoerWagner),
+            Err(CutError::WrongWeight {
+                required: 10,
+                minimum: 3
+            })
+        );
+    }
+
+    #[test]
+    fn disconnected() {
+        let cut = parse_graph("a: b\nc: d")
+            .minimum_cut(Backend::StoerWagner)
+            .unwrap();
+        assert_eq!(cut.weight, 0);
+        assert!(cut.edges.is_empty());
+        assert_eq!(cut.partitions, [vec!["a", "b"], vec!["c", "d"]]);
+    }
+
+    #[test]
+    fn backends_agree() {
+        for input in [
+            EXAMPLE,
+            "a: b*5 c*5\nb: c*5 d*2\nc: e\nd: e*2 f*2\ne: f*2",
+            "a: b\nc: d",
+            // every edge of a path is a minimum cut, the backends don't have to pick the same one
+            "a: b\nb: c\nc: d",
+        ] {
+            let graph = parse_graph(input);
+            let cuts = [Backend::StoerWagner, Backend::MaxFlow]
+                .map(|backend| graph.minimum_cut(backend).unwrap());
+            assert_eq!(cuts[0].weight, cuts[1].weight);
+            for cut in cuts {
+                assert!(graph.is_disconnected_by(&cut.edges));
+            }
+        }
+        assert_eq!(
+            parse_graph("a: b")
+                .minimum_cut(Backend::MaxFlow)
+                .unwrap()
+                .weight,
+            1
+        );
+        let graph = parse_graph("a: b\nb: c\nc: a");
+        assert!(!graph.is_disconnected_by(&[("a".to_string(), "b".to_string(), 1)]));
+        let cut = [
+            ("a".to_string(), "b".to_string(), 1),
+            ("a".to_string(), "x".to_string(), 1),
+            ("a".to_string(), "c".to_string(), 1),
+        ];
+        assert!(!graph.is_disconnected_by(&cut));
+    }
 }
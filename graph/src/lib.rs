@@ -0,0 +1,407 @@
+use std::{
+    collections::{HashMap, VecDeque},
+    error::Error,
+    fmt::{Debug, Display},
+    hash::Hash,
+};
+
+/// A directed graph with weighted edges. Nodes are kept in the order they were added, which makes
+/// the topological sort deterministic.
+#[derive(Debug, Clone)]
+pub struct Graph<T> {
+    nodes: Vec<T>,
+    indices: HashMap<T, usize>,
+    /// (to, weight) for every node
+    edges: Vec<Vec<(usize, i64)>>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Path<T> {
+    pub length: i64,
+    /// Every node along the path, including the first and the last
+    pub nodes: Vec<T>,
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum GraphError<T> {
+    /// Contains the nodes of one of the cycles, in order
+    GraphIsCyclical(Vec<T>),
+    Unreachable,
+}
+
+impl<T: Debug> Display for GraphError<T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            GraphError::GraphIsCyclical(cycle) => {
+                f.write_str("Graph is cyclical, topological sort cannot be completed: ")?;
+                for node in cycle.iter() {
+                    write!(f, "{:?} -> ", node)?;
+                }
+                write!(f, "{:?}", cycle[0])
+            }
+            GraphError::Unreachable => f.write_str("There is no path between the nodes"),
+        }
+    }
+}
+
+impl<T: Debug> Error for GraphError<T> {}
+
+impl<T> Default for Graph<T>
+where
+    T: Clone + Eq + Hash,
+{
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T> Graph<T>
+where
+    T: Clone + Eq + Hash,
+{
+    pub fn new() -> Self {
+        Self {
+            nodes: Vec::new(),
+            indices: HashMap::new(),
+            edges: Vec::new(),
+        }
+    }
+
+    /// Adds the node if it's not in the graph yet
+    pub fn add_node(&mut self, node: T) {
+        self.index_or_insert(node);
+    }
+
+    /// Adds both nodes if they're not in the graph yet
+    pub fn add_edge(&mut self, from: T, to: T, weight: i64) {
+        let from = self.index_or_insert(from);
+        let to = self.index_or_insert(to);
+        self.edges[from].push((to, weight));
+    }
+
+    pub fn len(&self) -> usize {
+        self.nodes.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.nodes.is_empty()
+    }
+
+    pub fn contains(&self, node: &T) -> bool {
+        self.indices.contains_key(node)
+    }
+
+    pub fn nodes(&self) -> impl Iterator<Item = &T> {
+        self.nodes.iter()
+    }
+
+    /// (from, to, weight) for every edge
+    pub fn edges(&self) -> impl Iterator<Item = (&T, &T, i64)> {
+        self.edges
+            .iter()
+            .enumerate()
+            .flat_map(move |(from, edges)| {
+                edges
+                    .iter()
+                    .map(move |&(to, weight)| (&self.nodes[from], &self.nodes[to], weight))
+            })
+    }
+
+    /// (to, weight) for every edge leaving the node
+    pub fn successors(&self, node: &T) -> impl Iterator<Item = (&T, i64)> {
+        self.indices
+            .get(node)
+            .into_iter()
+            .flat_map(move |&from| self.edges[from].iter())
+            .map(|&(to, weight)| (&self.nodes[to], weight))
+    }
+
+    /// Kahn's algorithm: every node comes after all nodes that have an edge to it
+    pub fn topological_sort(&self) -> Result<Vec<T>, GraphError<T>> {
+        let sorted = self.sorted_indices()?;
+        Ok(sorted.into_iter().map(|i| self.nodes[i].clone()).collect())
+    }
+
+    /// The path with the largest total weight, only works for a Directed Acyclical Graph
+    pub fn longest_path(&self, from: &T, to: &T) -> Result<Path<T>, GraphError<T>> {
+        self.dag_path(from, to, |new, old| new > old)
+    }
+
+    /// The path with the smallest total weight, only works for a Directed Acyclical Graph
+    pub fn shortest_path(&self, from: &T, to: &T) -> Result<Path<T>, GraphError<T>> {
+        self.dag_path(from, to, |new, old| new < old)
+    }
+
+    /// Tarjan's algorithm. Every component comes after all components it has edges to, so the
+    /// ones without any way out are first. Within a component, nodes are in the order they were
+    /// added.
+    pub fn strongly_connected_components(&self) -> Vec<Vec<T>> {
+        self.component_indices()
+            .into_iter()
+            .map(|component| {
+                component
+                    .into_iter()
+                    .map(|i| self.nodes[i].clone())
+                    .collect()
+            })
+            .collect()
+    }
+
+    fn index_or_insert(&mut self, node: T) -> usize {
+        if let Some(&index) = self.indices.get(&node) {
+            return index;
+        }
+        let index = self.nodes.len();
+        self.indices.insert(node.clone(), index);
+        self.nodes.push(node);
+        self.edges.push(Vec::new());
+        index
+    }
+
+    fn sorted_indices(&self) -> Result<Vec<usize>, GraphError<T>> {
+        // number of unresolved dependencies of every node
+        let mut in_degree = vec![0_usize; self.nodes.len()];
+        self.edges
+            .iter()
+            .flat_map(|edges| edges.iter())
+            .for_each(|&(to, _)| in_degree[to] += 1);
+
+        let mut no_deps: VecDeque<usize> = (0..self.nodes.len())
+            .filter(|&node| in_degree[node] == 0)
+            .collect();
+        let mut sorted = Vec::with_capacity(self.nodes.len());
+        while let Some(node) = no_deps.pop_front() {
+            sorted.push(node);
+            for &(dependent, _) in self.edges[node].iter() {
+                in_degree[dependent] -= 1;
+                if in_degree[dependent] == 0 {
+                    no_deps.push_back(dependent);
+                }
+            }
+        }
+
+        if sorted.len() < self.nodes.len() {
+            Err(GraphError::GraphIsCyclical(self.find_cycle(&in_degree)))
+        } else {
+            Ok(sorted)
+        }
+    }
+
+    /// Every node that Kahn's algorithm couldn't resolve still has a dependency that couldn't be
+    /// resolved, so walking back along those dependencies must end up going round a cycle
+    fn find_cycle(&self, in_degree: &[usize]) -> Vec<T> {
+        let mut dependency = vec![None; self.nodes.len()];
+        for (from, edges) in self.edges.iter().enumerate() {
+            if in_degree[from] > 0 {
+                for &(to, _) in edges.iter() {
+                    dependency[to] = Some(from);
+                }
+            }
+        }
+
+        let mut node = (0..self.nodes.len())
+            .find(|&node| in_degree[node] > 0)
+            .expect("There must be an unresolved node");
+        let mut seen_at = HashMap::new();
+        let mut walked = Vec::new();
+        while !seen_at.contains_key(&node) {
+            seen_at.insert(node, walked.len());
+            walked.push(node);
+            node = dependency[node].expect("Unresolved nodes have an unresolved dependency");
+        }
+        // we walked backwards, so turn it around
+        walked[seen_at[&node]..]
+            .iter()
+            .rev()
+            .map(|&i| self.nodes[i].clone())
+            .collect()
+    }
+
+    fn component_indices(&self) -> Vec<Vec<usize>> {
+        let n = self.nodes.len();
+        let mut index: Vec<Option<usize>> = vec![None; n];
+        let mut low_link = vec![0; n];
+        let mut on_stack = vec![false; n];
+        let mut stack = Vec::new();
+        let mut components = Vec::new();
+        let mut next_index = 0;
+        for root in 0..n {
+            if index[root].is_some() {
+                continue;
+            }
+            // (node, next edge to follow) instead of recursing, big graphs would overflow the stack
+            let mut calls = vec![(root, 0)];
+            index[root] = Some(next_index);
+            low_link[root] = next_index;
+            next_index += 1;
+            stack.push(root);
+            on_stack[root] = true;
+            while let Some((node, edge)) = calls.pop() {
+                if let Some(&(next, _)) = self.edges[node].get(edge) {
+                    calls.push((node, edge + 1));
+                    match index[next] {
+                        None => {
+                            index[next] = Some(next_index);
+                            low_link[next] = next_index;
+                            next_index += 1;
+                            stack.push(next);
+                            on_stack[next] = true;
+                            calls.push((next, 0));
+                        }
+                        Some(i) if on_stack[next] => low_link[node] = low_link[node].min(i),
+                        Some(_) => {}
+                    }
+                    continue;
+                }
+                // every edge has been followed
+                if let Some(&(parent, _)) = calls.last() {
+                    low_link[parent] = low_link[parent].min(low_link[node]);
+                }
+                if index[node] == Some(low_link[node]) {
+                    let mut component = Vec::new();
+                    loop {
+                        let member = stack.pop().expect("The node itself is still on the stack");
+                        on_stack[member] = false;
+                        component.push(member);
+                        if member == node {
+                            break;
+                        }
+                    }
+                    component.sort_unstable();
+                    components.push(component);
+                }
+            }
+        }
+        components
+    }
+
+    fn dag_path<F>(&self, from: &T, to: &T, is_better: F) -> Result<Path<T>, GraphError<T>>
+    where
+        F: Fn(i64, i64) -> bool,
+    {
+        let (Some(&from), Some(&to)) = (self.indices.get(from), self.indices.get(to)) else {
+            return Err(GraphError::Unreachable);
+        };
+        let mut lengths: Vec<Option<i64>> = vec![None; self.nodes.len()];
+        let mut previous = vec![None; self.nodes.len()];
+        lengths[from] = Some(0);
+        for node in self.sorted_indices()? {
+            let Some(length) = lengths[node] else {
+                continue; // not reachable from the start
+            };
+            for &(next, weight) in self.edges[node].iter() {
+                let new_length = length + weight;
+                if lengths[next].is_none_or(|old| is_better(new_length, old)) {
+                    lengths[next] = Some(new_length);
+                    previous[next] = Some(node);
+                }
+            }
+        }
+
+        let length = lengths[to].ok_or(GraphError::Unreachable)?;
+        let mut nodes = vec![self.nodes[to].clone()];
+        let mut current = to;
+        while let Some(node) = previous[current] {
+            nodes.push(self.nodes[node].clone());
+            current = node;
+        }
+        nodes.reverse();
+        Ok(Path { length, nodes })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn example() -> Graph<&'static str> {
+        let mut graph = Graph::new();
+        graph.add_edge("start", "a", 3);
+        graph.add_edge("start", "b", 1);
+        graph.add_edge("a", "c", 2);
+        graph.add_edge("b", "a", 1);
+        graph.add_edge("b", "c", 6);
+        graph.add_edge("c", "end", 1);
+        graph.add_node("island");
+        graph
+    }
+
+    #[test]
+    fn topological_sort() {
+        let graph = example();
+        let sorted = graph.topological_sort().unwrap();
+        assert_eq!(sorted, vec!["start", "island", "b", "a", "c", "end"]);
+        for (from, to, _) in graph.edges() {
+            let position = |node| sorted.iter().position(|n| n == node).unwrap();
+            assert!(position(from) < position(to));
+        }
+    }
+
+    #[test]
+    fn cycle() {
+        let mut graph = example();
+        graph.add_edge("c", "d", 1);
+        graph.add_edge("d", "b", 1);
+        let Err(GraphError::GraphIsCyclical(mut cycle)) = graph.topological_sort() else {
+            panic!("Graph should be cyclical");
+        };
+        // "a" and "end" are stuck as well, but they're not part of the cycle that was found
+        let start = cycle.iter().position(|&n| n == "b").unwrap();
+        cycle.rotate_left(start);
+        assert_eq!(cycle, vec!["b", "c", "d"]);
+
+        let error = graph.longest_path(&"start", &"end").unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "Graph is cyclical, topological sort cannot be completed: \"c\" -> \"d\" -> \"b\" -> \"c\""
+        );
+    }
+
+    #[test]
+    fn strongly_connected_components() {
+        let mut graph = example();
+        assert_eq!(
+            graph.strongly_connected_components(),
+            vec![
+                vec!["end"],
+                vec!["c"],
+                vec!["a"],
+                vec!["b"],
+                vec!["start"],
+                vec!["island"]
+            ]
+        );
+
+        graph.add_edge("c", "d", 1);
+        graph.add_edge("d", "b", 1);
+        graph.add_edge("island", "island", 0);
+        assert_eq!(
+            graph.strongly_connected_components(),
+            vec![
+                vec!["end"],
+                vec!["a", "b", "c", "d"],
+                vec!["start"],
+                vec!["island"]
+            ]
+        );
+    }
+
+    #[test]
+    fn paths() {
+        let graph = example();
+        let longest = graph.longest_path(&"start", &"end").unwrap();
+        assert_eq!(longest.length, 8);
+        assert_eq!(longest.nodes, vec!["start", "b", "c", "end"]);
+        let shortest = graph.shortest_path(&"start", &"end").unwrap();
+        assert_eq!(shortest.length, 5);
+        assert_eq!(shortest.nodes, vec!["start", "b", "a", "c", "end"]);
+        assert_eq!(
+            graph.longest_path(&"start", &"island"),
+            Err(GraphError::Unreachable)
+        );
+        assert_eq!(
+            graph.shortest_path(&"end", &"nowhere"),
+            Err(GraphError::Unreachable)
+        );
+    }
+}
@@ -0,0 +1,242 @@
+use std::{
+    cmp::Reverse,
+    collections::{BinaryHeap, HashMap},
+    error::Error,
+    fmt::Display,
+    hash::Hash,
+};
+
+/// The rules for getting from one state to the next. Implementing `heuristic` turns the search
+/// into A*, it must never overestimate the remaining cost to the nearest target.
+pub trait Movement {
+    type State: Clone + Eq + Hash;
+
+    /// Returns all states reachable in a single move, together with the cost of that move
+    fn neighbours(&self, state: &Self::State) -> Vec<(Self::State, usize)>;
+
+    fn heuristic(&self, _state: &Self::State) -> usize {
+        0
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Path<S> {
+    pub cost: usize,
+    /// Every state along the path, including the start and the target
+    pub states: Vec<S>,
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum SearchError {
+    Unreachable,
+}
+
+impl Display for SearchError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            SearchError::Unreachable => f.write_str("None of the targets can be reached"),
+        }
+    }
+}
+
+impl Error for SearchError {}
+
+/// Returns the cheapest path from any of the starts to the first state that is a target
+pub fn shortest_path<M, F>(
+    movement: &M,
+    starts: impl IntoIterator<Item = M::State>,
+    is_target: F,
+) -> Result<Path<M::State>, SearchError>
+where
+    M: Movement,
+    F: Fn(&M::State) -> bool,
+{
+    let mut search = Search::new(movement, starts);
+    while let Some(index) = search.next() {
+        if is_target(&search.states[index]) {
+            return Ok(search.path_to(index));
+        }
+    }
+    Err(SearchError::Unreachable)
+}
+
+/// Returns the cost of the cheapest path to every state reachable from any of the starts
+pub fn distances<M>(
+    movement: &M,
+    starts: impl IntoIterator<Item = M::State>,
+) -> HashMap<M::State, usize>
+where
+    M: Movement,
+{
+    let mut search = Search::new(movement, starts);
+    while search.next().is_some() {}
+    search.states.into_iter().zip(search.costs).collect()
+}
+
+/// Dijkstra/A* state, states are referred to by their index in `states`
+struct Search<'a, M: Movement> {
+    movement: &'a M,
+    indices: HashMap<M::State, usize>,
+    states: Vec<M::State>,
+    costs: Vec<usize>,
+    previous: Vec<Option<usize>>,
+    // (estimated total cost, cost so far, index)
+    queue: BinaryHeap<Reverse<(usize, usize, usize)>>,
+}
+
+impl<'a, M: Movement> Search<'a, M> {
+    fn new(movement: &'a M, starts: impl IntoIterator<Item = M::State>) -> Self {
+        let mut search = Self {
+            movement,
+            indices: HashMap::new(),
+            states: Vec::new(),
+            costs: Vec::new(),
+            previous: Vec::new(),
+            queue: BinaryHeap::new(),
+        };
+        for start in starts {
+            search.visit(start, 0, None);
+        }
+        search
+    }
+
+    /// Records `state` if it's new or cheaper than before and queues it
+    fn visit(&mut self, state: M::State, cost: usize, previous: Option<usize>) {
+        let index = match self.indices.get(&state) {
+            Some(&index) => {
+                if cost >= self.costs[index] {
+                    return;
+                }
+                self.costs[index] = cost;
+                self.previous[index] = previous;
+                index
+            }
+            None => {
+                let index = self.states.len();
+                self.indices.insert(state.clone(), index);
+                self.states.push(state);
+                self.costs.push(cost);
+                self.previous.push(previous);
+                index
+            }
+        };
+        let estimate = cost + self.movement.heuristic(&self.states[index]);
+        self.queue.push(Reverse((estimate, cost, index)));
+    }
+
+    /// Settles the next cheapest state and queues its neighbours, returns its index
+    fn next(&mut self) -> Option<usize> {
+        while let Some(Reverse((_, cost, index))) = self.queue.pop() {
+            if cost > self.costs[index] {
+                continue; // we've already found a cheaper way to get here
+            }
+            for (neighbour, step) in self.movement.neighbours(&self.states[index]) {
+                self.visit(neighbour, cost + step, Some(index));
+            }
+            return Some(index);
+        }
+        None
+    }
+
+    fn path_to(&self, index: usize) -> Path<M::State> {
+        let mut states = vec![self.states[index].clone()];
+        let mut current = index;
+        while let Some(previous) = self.previous[current] {
+            states.push(self.states[previous].clone());
+            current = previous;
+        }
+        states.reverse();
+        Path {
+            cost: self.costs[index],
+            states,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Walks over the '.' tiles of a grid, every step costs 1
+    struct Walk {
+        grid: Vec<Vec<char>>,
+        target: (usize, usize),
+        a_star: bool,
+    }
+
+    impl Walk {
+        fn new(input: &str, a_star: bool) -> Self {
+            let grid: Vec<Vec<char>> = input.lines().map(|l| l.chars().collect()).collect();
+            let target = (grid[0].len() - 1, grid.len() - 1);
+            Self {
+                grid,
+                target,
+                a_star,
+            }
+        }
+    }
+
+    impl Movement for Walk {
+        type State = (usize, usize);
+
+        fn neighbours(&self, &(x, y): &Self::State) -> Vec<(Self::State, usize)> {
+            [
+                (x.wrapping_sub(1), y),
+                (x + 1, y),
+                (x, y.wrapping_sub(1)),
+                (x, y + 1),
+            ]
+            .into_iter()
+            .filter(|&(x, y)| self.grid.get(y).and_then(|row| row.get(x)) == Some(&'.'))
+            .map(|point| (point, 1))
+            .collect()
+        }
+
+        fn heuristic(&self, &(x, y): &Self::State) -> usize {
+            if self.a_star {
+                self.target.0.abs_diff(x) + self.target.1.abs_diff(y)
+            } else {
+                0
+            }
+        }
+    }
+
+    const MAZE: &str = "..#....
+.##.##.
+....#..
+.##...#
+...#...";
+
+    #[test]
+    fn dijkstra_and_a_star() {
+        for a_star in [false, true] {
+            let walk = Walk::new(MAZE, a_star);
+            let path = shortest_path(&walk, [(0, 0)], |&p| p == walk.target).unwrap();
+            assert_eq!(path.cost, 10);
+            assert_eq!(path.states.len(), 11);
+            assert_eq!(path.states.first(), Some(&(0, 0)));
+            assert_eq!(path.states.last(), Some(&(6, 4)));
+            assert!(path
+                .states
+                .windows(2)
+                .all(|w| w[0].0.abs_diff(w[1].0) + w[0].1.abs_diff(w[1].1) == 1));
+        }
+    }
+
+    #[test]
+    fn unreachable() {
+        let walk = Walk::new("..#.\n.##.\n#...\n..#.", false);
+        let result = shortest_path(&walk, [(0, 0)], |&p| p == walk.target);
+        assert_eq!(result, Err(SearchError::Unreachable));
+    }
+
+    #[test]
+    fn all_distances() {
+        let walk = Walk::new(MAZE, false);
+        let distances = distances(&walk, [(0, 0)]);
+        assert_eq!(distances.get(&(0, 0)), Some(&0));
+        assert_eq!(distances.get(&(3, 0)), Some(&7));
+        assert_eq!(distances.get(&(6, 4)), Some(&10));
+        assert_eq!(distances.get(&(2, 0)), None);
+    }
+}